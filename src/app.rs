@@ -1,12 +1,18 @@
 use crate::io;
 use crate::lib::{
-    Action, AddProblemScreen, GraphScreen, HomeScreen, MenuScreen, Screen, ScreenAction, View,
-    ViewAllProblemsScreen,
+    Action, AddProblemScreen, DeckScreen, DueQueueScreen, ForecastScreen, GraphScreen, HomeScreen,
+    MenuScreen, NotesScreen, ReviewSessionScreen, Screen, ScreenAction, SimulatorScreen,
+    SolutionScreen, TagScreen, View, ViewAllProblemsScreen,
 };
 use crossterm::event::{self, Event, KeyEventKind};
 use ratatui::DefaultTerminal;
 use ratatui::Frame;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// How long to wait for input before redrawing anyway, so running
+/// stopwatches keep counting on screen.
+const TICK_RATE: Duration = Duration::from_millis(250);
 
 #[derive(Debug)]
 pub struct App<'a> {
@@ -24,15 +30,26 @@ impl<'a> App<'a> {
         }
     }
     pub fn handle_events(&mut self) -> io::Result<()> {
+        if !event::poll(TICK_RATE)? {
+            return Ok(());
+        }
         if let Event::Key(key_event) = event::read()?
             && key_event.kind == KeyEventKind::Press
         {
             let view: &mut dyn View = match &mut self.current_screen {
                 Screen::HomeScreen(home) => home,
                 Screen::MenuScreen(second) => second,
-                Screen::AddProblemScreen(add) => add,
+                Screen::AddProblemScreen(add) => add.as_mut(),
                 Screen::ViewAllProblemsScreen(problem_screen) => problem_screen,
                 Screen::GraphScreen(graph) => graph,
+                Screen::DueQueueScreen(due) => due,
+                Screen::ReviewSessionScreen(session) => session,
+                Screen::ForecastScreen(forecast) => forecast,
+                Screen::SimulatorScreen(simulator) => simulator,
+                Screen::TagScreen(tags) => tags,
+                Screen::NotesScreen(notes) => notes.as_mut(),
+                Screen::SolutionScreen(solutions) => solutions,
+                Screen::DeckScreen(decks) => decks,
             };
 
             let action = view.handle_key_event(key_event);
@@ -58,12 +75,59 @@ impl<'a> App<'a> {
                 ScreenAction::MenuNext => self.move_menu_selection(1),
                 ScreenAction::MenuPrev => self.move_menu_selection(-1),
                 ScreenAction::MenuSelect => self.select_menu_item(),
+                ScreenAction::ViewSolutions => {}
             }
-        } else if let Screen::ViewAllProblemsScreen(_problem_screen) = &mut self.current_screen {
+        } else if let Screen::ViewAllProblemsScreen(problem_screen) = &mut self.current_screen {
             match action {
                 ScreenAction::MenuNext => self.move_menu_selection(1),
                 ScreenAction::MenuPrev => self.move_menu_selection(-1),
-                ScreenAction::MenuSelect => self.select_menu_item(),
+                // Enter opens the selected problem's notes
+                ScreenAction::MenuSelect => {
+                    if let Some(problem_id) = problem_screen.selected_id() {
+                        let filter = problem_screen.filter;
+                        self.current_screen = Screen::NotesScreen(Box::new(NotesScreen::new(
+                            Arc::clone(&self.db),
+                            problem_id,
+                            filter,
+                        )));
+                    }
+                }
+                ScreenAction::ViewSolutions => {
+                    if let Some(problem_id) = problem_screen.selected_id() {
+                        let filter = problem_screen.filter;
+                        self.current_screen = Screen::SolutionScreen(SolutionScreen::new(
+                            Arc::clone(&self.db),
+                            problem_id,
+                            filter,
+                        ));
+                    }
+                }
+            }
+        } else if let Screen::DueQueueScreen(_due) = &mut self.current_screen {
+            match action {
+                ScreenAction::MenuNext => self.move_menu_selection(1),
+                ScreenAction::MenuPrev => self.move_menu_selection(-1),
+                // Enter on the queue starts working through it
+                ScreenAction::MenuSelect => self.switch_screen_menu(4),
+                ScreenAction::ViewSolutions => {}
+            }
+        } else if let Screen::SimulatorScreen(_simulator) = &mut self.current_screen {
+            match action {
+                ScreenAction::MenuNext => self.move_menu_selection(1),
+                ScreenAction::MenuPrev => self.move_menu_selection(-1),
+                ScreenAction::MenuSelect | ScreenAction::ViewSolutions => {}
+            }
+        } else if let Screen::TagScreen(_tags) = &mut self.current_screen {
+            match action {
+                ScreenAction::MenuNext => self.move_menu_selection(1),
+                ScreenAction::MenuPrev => self.move_menu_selection(-1),
+                ScreenAction::MenuSelect | ScreenAction::ViewSolutions => {}
+            }
+        } else if let Screen::DeckScreen(_decks) = &mut self.current_screen {
+            match action {
+                ScreenAction::MenuNext => self.move_menu_selection(1),
+                ScreenAction::MenuPrev => self.move_menu_selection(-1),
+                ScreenAction::MenuSelect | ScreenAction::ViewSolutions => {}
             }
         }
     }
@@ -82,12 +146,48 @@ impl<'a> App<'a> {
         } else if let Screen::ViewAllProblemsScreen(problem_screen) = &mut self.current_screen {
             let i = match problem_screen.list_state.selected() {
                 Some(i) => {
-                    let len = problem_screen.items.len();
+                    let len = problem_screen.items.len().max(1);
                     (i as isize + direction).rem_euclid(len as isize) as usize
                 }
                 None => 0,
             };
             problem_screen.list_state.select(Some(i));
+        } else if let Screen::DueQueueScreen(due) = &mut self.current_screen {
+            let i = match due.list_state.selected() {
+                Some(i) => {
+                    let len = due.items.len().max(1);
+                    (i as isize + direction).rem_euclid(len as isize) as usize
+                }
+                None => 0,
+            };
+            due.list_state.select(Some(i));
+        } else if let Screen::SimulatorScreen(simulator) = &mut self.current_screen {
+            let i = match simulator.list_state.selected() {
+                Some(i) => {
+                    let len = simulator.items.len().max(1);
+                    (i as isize + direction).rem_euclid(len as isize) as usize
+                }
+                None => 0,
+            };
+            simulator.list_state.select(Some(i));
+        } else if let Screen::TagScreen(tags) = &mut self.current_screen {
+            let i = match tags.list_state.selected() {
+                Some(i) => {
+                    let len = tags.items.len().max(1);
+                    (i as isize + direction).rem_euclid(len as isize) as usize
+                }
+                None => 0,
+            };
+            tags.list_state.select(Some(i));
+        } else if let Screen::DeckScreen(decks) = &mut self.current_screen {
+            let i = match decks.list_state.selected() {
+                Some(i) => {
+                    let len = decks.items.len().max(1);
+                    (i as isize + direction).rem_euclid(len as isize) as usize
+                }
+                None => 0,
+            };
+            decks.list_state.select(Some(i));
         }
     }
 
@@ -101,6 +201,11 @@ impl<'a> App<'a> {
                 1 => self.switch_screen_menu(1),
                 2 => self.switch_screen_menu(2),
                 3 => self.switch_screen_menu(3),
+                4 => self.switch_screen_menu(4),
+                5 => self.switch_screen_menu(5),
+                6 => self.switch_screen_menu(6),
+                7 => self.switch_screen_menu(7),
+                8 => self.switch_screen_menu(8),
                 _ => {}
             }
         }
@@ -110,13 +215,31 @@ impl<'a> App<'a> {
         match index {
             0 => {
                 self.current_screen =
-                    Screen::AddProblemScreen(AddProblemScreen::new(Arc::clone(&self.db)))
+                    Screen::AddProblemScreen(Box::new(AddProblemScreen::new(Arc::clone(&self.db))))
             }
             1 => {
                 self.current_screen =
                     Screen::ViewAllProblemsScreen(ViewAllProblemsScreen::new(Arc::clone(&self.db)))
             }
             2 => self.current_screen = Screen::GraphScreen(GraphScreen::new(Arc::clone(&self.db))),
+            3 => {
+                self.current_screen =
+                    Screen::DueQueueScreen(DueQueueScreen::new(Arc::clone(&self.db)))
+            }
+            4 => {
+                self.current_screen =
+                    Screen::ReviewSessionScreen(ReviewSessionScreen::new(Arc::clone(&self.db)))
+            }
+            5 => {
+                self.current_screen =
+                    Screen::ForecastScreen(ForecastScreen::new(Arc::clone(&self.db)))
+            }
+            6 => {
+                self.current_screen =
+                    Screen::SimulatorScreen(SimulatorScreen::new(Arc::clone(&self.db)))
+            }
+            7 => self.current_screen = Screen::TagScreen(TagScreen::new(Arc::clone(&self.db))),
+            8 => self.current_screen = Screen::DeckScreen(DeckScreen::new(Arc::clone(&self.db))),
             _ => {}
         }
     }
@@ -124,19 +247,51 @@ impl<'a> App<'a> {
     pub fn switch_screens(&mut self) {
         match self.current_screen {
             Screen::HomeScreen(_) => {
-                self.current_screen = Screen::MenuScreen(MenuScreen::default());
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)));
             }
             Screen::MenuScreen(_) => {
                 self.current_screen = Screen::HomeScreen(HomeScreen::default());
             }
             Screen::AddProblemScreen(_) => {
-                self.current_screen = Screen::MenuScreen(MenuScreen::default());
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)));
             }
             Screen::ViewAllProblemsScreen(_) => {
-                self.current_screen = Screen::MenuScreen(MenuScreen::default());
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)));
             }
             Screen::GraphScreen(_) => {
-                self.current_screen = Screen::MenuScreen(MenuScreen::default())
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::DueQueueScreen(_) => {
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::ReviewSessionScreen(_) => {
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::ForecastScreen(_) => {
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::SimulatorScreen(_) => {
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::TagScreen(_) => {
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::DeckScreen(_) => {
+                self.current_screen = Screen::MenuScreen(MenuScreen::new(Arc::clone(&self.db)))
+            }
+            Screen::NotesScreen(ref notes) => {
+                self.current_screen = Screen::ViewAllProblemsScreen(ViewAllProblemsScreen::showing(
+                    Arc::clone(&self.db),
+                    notes.filter,
+                    notes.problem_id,
+                ))
+            }
+            Screen::SolutionScreen(ref solutions) => {
+                self.current_screen = Screen::ViewAllProblemsScreen(ViewAllProblemsScreen::showing(
+                    Arc::clone(&self.db),
+                    solutions.filter,
+                    solutions.problem_id,
+                ))
             }
         }
     }
@@ -158,9 +313,17 @@ impl<'a> App<'a> {
         let view: &dyn View = match &self.current_screen {
             Screen::HomeScreen(home) => home,
             Screen::MenuScreen(second) => second,
-            Screen::AddProblemScreen(add) => add,
+            Screen::AddProblemScreen(add) => add.as_ref(),
             Screen::ViewAllProblemsScreen(problem_screen) => problem_screen,
             Screen::GraphScreen(graph) => graph,
+            Screen::DueQueueScreen(due) => due,
+            Screen::ReviewSessionScreen(session) => session,
+            Screen::ForecastScreen(forecast) => forecast,
+            Screen::SimulatorScreen(simulator) => simulator,
+            Screen::TagScreen(tags) => tags,
+            Screen::NotesScreen(notes) => notes.as_ref(),
+            Screen::SolutionScreen(solutions) => solutions,
+            Screen::DeckScreen(decks) => decks,
         };
 
         view.draw(frame);
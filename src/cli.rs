@@ -0,0 +1,276 @@
+use crate::db;
+use crate::deck_file;
+use crate::lib::Pause;
+use crate::scheduler::{self, fsrs, graduation, leitner, limits, load_balance};
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use std::path::Path;
+
+const USAGE: &str = "Usage: srl_r [command]
+
+Without a command the TUI starts.
+
+Commands:
+  config <key> [value]     Show or change a per-database setting
+  fsrs-fit [retention]     Fit FSRS weights to the review history
+  simulate [days]          Compare schedulers on the review history (default 30 days)
+  pause list               List declared pauses
+  pause add <start> <end>  Pause reviews between two YYYY-MM-DD dates (inclusive)
+  pause remove <start>     Forget the pause starting on a YYYY-MM-DD date
+  deck list                List decks and how many problems each has
+  deck import <file>       Merge a .toml or .json deck file into the database
+  deck export <deck> <file>
+                           Write a deck to a .toml or .json file, without progress
+  help                     Show this message
+
+Settings:
+  scheduler                sm2 (default), fsrs or leitner
+  fsrs_retention           Target retention for FSRS, 0.7-0.97 (default 0.9)
+  fsrs_weights             Comma-separated FSRS weights, written by fsrs-fit
+  leitner_intervals        Comma-separated days per Leitner box, up to 20 (default 1,2,4,8,16)
+  leitner_boxes            Number of Leitner boxes, 1-20 (default: one per interval)
+  mastery_streak           Consecutive top ratings that master a problem (default 2)
+  mastery_rating           Lowest rating that counts towards mastery, 1-5 (default 5)
+  load_balance             on (default) or off: spread due dates to the least busy nearby day
+  fuzz_percent             Size of the load balancing window as % of the interval (default 5)
+  fuzz_max_days            Largest number of days a due date may move (default 4)
+  fuzz_seed                Seed for breaking ties between equally busy days (default 0)
+  max_new_per_day          New problems introduced per day, 0 for no limit (default 0)
+  max_reviews_per_day      Reviews shown per day, 0 for no limit (default 0)
+  heatmap_suspended        on (default) or off: count suspended problems on the heatmap";
+
+/// Runs a one-shot command instead of the TUI.
+pub fn run(db: &Connection, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args.first().map(String::as_str) {
+        Some("config") => config(db, &args[1..]),
+        Some("fsrs-fit") => fsrs_fit(db, args.get(1)),
+        Some("pause") => pause(db, &args[1..]),
+        Some("deck") => deck(db, &args[1..]),
+        Some("simulate") => simulate(db, args.get(1)),
+        Some("help") | Some("--help") | Some("-h") => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        Some(other) => Err(format!("unknown command '{other}'\n\n{USAGE}").into()),
+        None => Ok(()),
+    }
+}
+
+fn config(db: &Connection, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args {
+        [key] => {
+            match db::get_setting(db, key)? {
+                Some(value) => println!("{key} = {value}"),
+                None => println!("{key} is not set"),
+            }
+            Ok(())
+        }
+        [key, value] => {
+            validate_setting(key, value)?;
+            db::set_setting(db, key, value)?;
+            println!("{key} = {value}");
+            Ok(())
+        }
+        _ => Err(format!("expected: config <key> [value]\n\n{USAGE}").into()),
+    }
+}
+
+fn validate_setting(key: &str, value: &str) -> Result<(), String> {
+    let valid = match key {
+        "scheduler" => scheduler::SCHEDULER_NAMES.contains(&value),
+        "fsrs_retention" => fsrs::parse_retention(value).is_some(),
+        "fsrs_weights" => fsrs::parse_weights(value).is_some(),
+        "leitner_intervals" => leitner::parse_intervals(value).is_some(),
+        "leitner_boxes" => leitner::parse_box_count(value).is_some(),
+        "mastery_streak" => graduation::parse_streak(value).is_some(),
+        "mastery_rating" => graduation::parse_min_rating(value).is_some(),
+        "load_balance" | "heatmap_suspended" => db::parse_enabled(value).is_some(),
+        "fuzz_percent" => load_balance::parse_percent(value).is_some(),
+        "fuzz_max_days" => load_balance::parse_spread(value).is_some(),
+        "fuzz_seed" => load_balance::parse_seed(value).is_some(),
+        "max_new_per_day" | "max_reviews_per_day" => limits::parse_limit(value).is_some(),
+        _ => return Err(format!("unknown setting '{key}'")),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("invalid value '{value}' for {key}"))
+    }
+}
+
+fn fsrs_fit(db: &Connection, retention: Option<&String>) -> Result<(), Box<dyn std::error::Error>> {
+    let retention = match retention {
+        Some(value) => fsrs::parse_retention(value)
+            .ok_or_else(|| format!("retention must be between 0.7 and 0.97, got '{value}'"))?,
+        None => fsrs::FsrsParams::load(db)?.retention,
+    };
+
+    let reviews = db::load_reviews(db)?;
+    let Some(report) = fsrs::fit(&reviews, retention) else {
+        return Err(format!(
+            "not enough review history to fit FSRS (need at least {} repeat reviews)",
+            fsrs::MIN_FIT_REVIEWS
+        )
+        .into());
+    };
+    report.params.save(db)?;
+
+    println!("Fitted FSRS weights from {} reviews", report.reviews_used);
+    println!(
+        "Log loss: {:.4} -> {:.4}",
+        report.loss_before, report.loss_after
+    );
+    println!("Target retention: {}", report.params.retention);
+    if db::get_setting(db, "scheduler")?.as_deref() != Some("fsrs") {
+        println!("Run `srl_r config scheduler fsrs` to schedule with these weights");
+    }
+    Ok(())
+}
+
+fn simulate(db: &Connection, days: Option<&String>) -> Result<(), Box<dyn std::error::Error>> {
+    let days = match days {
+        Some(value) => value
+            .parse::<usize>()
+            .ok()
+            .filter(|days| (1..=365).contains(days))
+            .ok_or_else(|| format!("days must be between 1 and 365, got '{value}'"))?,
+        None => 30,
+    };
+
+    let results = scheduler::simulate::run(db, days)?;
+    println!(
+        "{:<32} {:>10} {:>12} {:>9} {:>10}",
+        "Scheduler", "Per day", "Next days", "Peak", "Retention"
+    );
+    for result in results {
+        let total: u64 = result.daily_reviews.iter().sum();
+        let peak = result.daily_reviews.iter().max().copied().unwrap_or(0);
+        println!(
+            "{:<32} {:>10.1} {:>12} {:>9} {:>9.1}%",
+            result.name,
+            result.steady_per_day,
+            total,
+            peak,
+            result.expected_retention * 100.0
+        );
+    }
+    println!();
+    println!(
+        "Per day: long run reviews per day; next days / peak: reviews over the next {days} days,"
+    );
+    println!("load balanced and skipping pauses as real reviews are");
+    if db::get_setting(db, "fsrs_weights")?.is_some() {
+        println!("Retention: estimated with the FSRS weights saved by fsrs-fit");
+    } else {
+        println!(
+            "Retention: estimated with the default FSRS weights; run fsrs-fit to use your own"
+        );
+    }
+    Ok(())
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    value
+        .parse::<NaiveDate>()
+        .map_err(|_| format!("expected a YYYY-MM-DD date, got '{value}'"))
+}
+
+fn pause(db: &Connection, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args {
+        [command] if command == "list" => {
+            let pauses = db::load_pauses(db)?;
+            if pauses.is_empty() {
+                println!("No pauses declared");
+            }
+            for pause in pauses {
+                println!(
+                    "{} to {} ({} days)",
+                    pause.start,
+                    pause.end,
+                    pause.len_days()
+                );
+            }
+            Ok(())
+        }
+        [command, start, end] if command == "add" => {
+            let pause = Pause {
+                start: parse_date(start)?,
+                end: parse_date(end)?,
+            };
+            if pause.end < pause.start {
+                return Err("a pause cannot end before it starts".into());
+            }
+            let moved = scheduler::pause::add_pause(db, &pause)?;
+            println!(
+                "Paused {} to {}, moved {moved} problem(s) {} days later",
+                pause.start,
+                pause.end,
+                pause.len_days()
+            );
+            Ok(())
+        }
+        [command, start] if command == "remove" => {
+            if db::delete_pause(db, parse_date(start)?)? {
+                println!(
+                    "Removed the pause starting {start}; due dates already moved stay as they are"
+                );
+                Ok(())
+            } else {
+                Err(format!("no pause starts on {start}").into())
+            }
+        }
+        _ => Err(
+            format!("expected: pause list | add <start> <end> | remove <start>\n\n{USAGE}").into(),
+        ),
+    }
+}
+
+fn deck(db: &Connection, args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    match args {
+        [command] if command == "list" => {
+            let decks = db::load_decks(db)?;
+            if decks.is_empty() {
+                println!("No decks yet");
+            }
+            for deck in decks {
+                println!("{} ({} problems)", deck.name, deck.problems);
+            }
+            Ok(())
+        }
+        [command, file] if command == "import" => {
+            let deck = deck_file::read(Path::new(file))?;
+            let summary = deck_file::import(db, &deck)?;
+            println!(
+                "{} {}: {} new problem(s), {} already in the database",
+                if summary.deck_created {
+                    "Created"
+                } else {
+                    "Merged into"
+                },
+                db::normalize_name(&deck.name),
+                summary.added,
+                summary.existing
+            );
+            for warning in summary.warnings {
+                println!("  {warning}");
+            }
+            Ok(())
+        }
+        [command, name, file] if command == "export" => {
+            let deck_id = db::find_deck_id(db, name)?;
+            let deck = db::load_decks(db)?
+                .into_iter()
+                .find(|deck| Some(deck.id) == deck_id)
+                .ok_or_else(|| format!("no deck called '{name}'"))?;
+            deck_file::write(Path::new(file), &deck_file::export(db, &deck)?)?;
+            println!(
+                "Wrote {} problem(s) of {} to {file}",
+                deck.problems, deck.name
+            );
+            Ok(())
+        }
+        _ => Err(
+            format!("expected: deck list | import <file> | export <deck> <file>\n\n{USAGE}").into(),
+        ),
+    }
+}
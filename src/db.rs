@@ -0,0 +1,1223 @@
+use crate::lib::{
+    Attempt, Deck, Difficulty, Pause, Problem, ProblemMetadata, Rating, Relation, RelationKind,
+    Review, ScheduleState, Tag,
+};
+use crate::scheduler;
+use crate::scheduler::graduation::GraduationRule;
+use crate::scheduler::load_balance::LoadBalancer;
+use crate::scheduler::pause;
+use chrono::{DateTime, NaiveDate, SecondsFormat, Utc};
+use rusqlite::types::{FromSql, FromSqlError, FromSqlResult, ToSql, ToSqlOutput, Type, ValueRef};
+use rusqlite::{Connection, OptionalExtension, Row};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Column list matching [`problem_from_row`].
+pub const PROBLEM_COLUMNS: &str = "id, problem_name, problem_rating, entry_date, due_date,
+    ease_factor, interval_days, repetitions, stability, difficulty, leitner_box,
+    top_streak, mastered, suspended, url, platform, problem_number, official_difficulty";
+
+/// Builds a [`Problem`] from a row selected with [`PROBLEM_COLUMNS`].
+pub fn problem_from_row(row: &Row) -> rusqlite::Result<Problem> {
+    let entry_date = timestamp_column(row, 3)?;
+    let due_date = match row.get::<_, Option<String>>(4)? {
+        Some(_) => Some(timestamp_column(row, 4)?),
+        None => None,
+    };
+    Ok(Problem {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        rating: row.get(2)?,
+        entry_date,
+        due_date,
+        state: ScheduleState {
+            ease_factor: row.get(5)?,
+            interval: row.get(6)?,
+            repetitions: row.get(7)?,
+            stability: row.get(8)?,
+            difficulty: row.get(9)?,
+            leitner_box: row.get(10)?,
+            last_review: Some(entry_date),
+        },
+        top_streak: row.get(11)?,
+        mastered: row.get(12)?,
+        suspended: row.get(13)?,
+        metadata: ProblemMetadata {
+            url: row.get(14)?,
+            platform: row.get(15)?,
+            number: row.get(16)?,
+            difficulty: row
+                .get::<_, Option<String>>(17)?
+                .and_then(|difficulty| Difficulty::parse(&difficulty)),
+        },
+    })
+}
+
+/// How every timestamp is stored: RFC 3339 in UTC with milliseconds, so the
+/// text sorts in time order.
+pub fn timestamp(at: &DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Reads a stored timestamp. Also accepts chrono's display format, which
+/// older databases used.
+pub fn parse_timestamp(text: &str) -> Option<DateTime<Utc>> {
+    text.parse::<DateTime<Utc>>().ok()
+}
+
+fn timestamp_column(row: &Row, index: usize) -> rusqlite::Result<DateTime<Utc>> {
+    let text: String = row.get(index)?;
+    parse_timestamp(&text).ok_or_else(|| {
+        rusqlite::Error::FromSqlConversionFailure(
+            index,
+            Type::Text,
+            format!("unreadable timestamp {text:?}").into(),
+        )
+    })
+}
+
+impl Rating {
+    pub const ALL: [Rating; 5] = [
+        Rating::Blackout,
+        Rating::Incorrect,
+        Rating::Difficult,
+        Rating::Hesitant,
+        Rating::Perfect,
+    ];
+
+    pub fn from_value(value: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|rating| rating.value() == value)
+    }
+
+    /// Reads a typed rating such as "4".
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::from_value(value.trim().parse().ok()?)
+    }
+
+    pub fn value(self) -> u8 {
+        self as u8
+    }
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+impl ToSql for Rating {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(self.value().into())
+    }
+}
+
+impl FromSql for Rating {
+    fn column_result(value: ValueRef<'_>) -> FromSqlResult<Self> {
+        let number = i64::column_result(value)?;
+        u8::try_from(number)
+            .ok()
+            .and_then(Rating::from_value)
+            .ok_or(FromSqlError::OutOfRange(number))
+    }
+}
+
+impl Difficulty {
+    /// Accepts the label in any case, or any start of it such as "med".
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase();
+        if value.is_empty() {
+            return None;
+        }
+        [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+            .into_iter()
+            .find(|difficulty| difficulty.as_str().to_ascii_lowercase().starts_with(&value))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+}
+
+impl RelationKind {
+    /// Accepts the stored name in any case, or any start of it such as
+    /// "prereq". "followup" and "follow_up" also read as a follow-up.
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim().to_ascii_lowercase().replace('_', "-");
+        if value.is_empty() {
+            return None;
+        }
+        if value == "followup" {
+            return Some(RelationKind::FollowUp);
+        }
+        [
+            RelationKind::Similar,
+            RelationKind::FollowUp,
+            RelationKind::Prerequisite,
+        ]
+        .into_iter()
+        .find(|kind| kind.as_str().starts_with(&value))
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RelationKind::Similar => "similar",
+            RelationKind::FollowUp => "follow-up",
+            RelationKind::Prerequisite => "prerequisite",
+        }
+    }
+}
+
+impl Relation {
+    /// How the linked problem stands to this one, e.g. "Follow-up of".
+    pub fn label(&self) -> &'static str {
+        match (self.kind, self.incoming) {
+            (RelationKind::Similar, _) => "Similar to",
+            (RelationKind::FollowUp, false) => "Follow-up of",
+            (RelationKind::FollowUp, true) => "Followed up by",
+            (RelationKind::Prerequisite, false) => "Prerequisite of",
+            (RelationKind::Prerequisite, true) => "Builds on",
+        }
+    }
+
+    /// How well the linked problem suits practising after struggling with this
+    /// one, best first: what it builds on, then what it follows up, then
+    /// similar problems. `None` for harder ones that build on this one.
+    pub fn step_back_rank(&self) -> Option<u8> {
+        match (self.kind, self.incoming) {
+            (RelationKind::Prerequisite, true) => Some(0),
+            (RelationKind::FollowUp, false) => Some(1),
+            (RelationKind::Similar, _) => Some(2),
+            _ => None,
+        }
+    }
+}
+
+/// Trims a problem name and collapses runs of whitespace, so "Two Sum " and
+/// "Two  Sum" are stored as "Two Sum". Case is kept; lookups ignore it.
+pub fn normalize_name(problem_name: &str) -> String {
+    problem_name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Looks a problem up by name, ignoring case and stray whitespace.
+pub fn find_problem_id(db: &Connection, problem_name: &str) -> rusqlite::Result<Option<i64>> {
+    db.query_row(
+        "SELECT id FROM user_problems WHERE problem_name = ?1",
+        [normalize_name(problem_name)],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub fn check_row_exists(db: &Connection, problem_name: &str) -> rusqlite::Result<bool> {
+    Ok(find_problem_id(db, problem_name)?.is_some())
+}
+
+pub fn load_problem(db: &Connection, problem_id: i64) -> rusqlite::Result<Option<Problem>> {
+    db.query_row(
+        &format!("SELECT {PROBLEM_COLUMNS} FROM user_problems WHERE id = ?1"),
+        [problem_id],
+        problem_from_row,
+    )
+    .optional()
+}
+
+/// Stores the metadata fields that are set, leaving the others as they were.
+pub fn set_metadata(
+    db: &Connection,
+    problem_id: i64,
+    metadata: &ProblemMetadata,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE user_problems SET url = COALESCE(?1, url), platform = COALESCE(?2, platform),
+            problem_number = COALESCE(?3, problem_number),
+            official_difficulty = COALESCE(?4, official_difficulty)
+         WHERE id = ?5",
+        (
+            &metadata.url,
+            &metadata.platform,
+            &metadata.number,
+            metadata.difficulty.map(|difficulty| difficulty.as_str()),
+            problem_id,
+        ),
+    )?;
+    Ok(())
+}
+
+/// Stores only the metadata fields the problem doesn't have yet, so details
+/// entered by hand win over imported ones.
+pub fn fill_metadata(
+    db: &Connection,
+    problem_id: i64,
+    metadata: &ProblemMetadata,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE user_problems SET url = COALESCE(url, ?1), platform = COALESCE(platform, ?2),
+            problem_number = COALESCE(problem_number, ?3),
+            official_difficulty = COALESCE(official_difficulty, ?4)
+         WHERE id = ?5",
+        (
+            &metadata.url,
+            &metadata.platform,
+            &metadata.number,
+            metadata.difficulty.map(|difficulty| difficulty.as_str()),
+            problem_id,
+        ),
+    )?;
+    Ok(())
+}
+
+/// A problem's markdown notes, empty if it has none.
+pub fn load_notes(db: &Connection, problem_id: i64) -> rusqlite::Result<String> {
+    db.query_row(
+        "SELECT notes FROM user_problems WHERE id = ?1",
+        [problem_id],
+        |row| row.get(0),
+    )
+}
+
+/// Notes of every problem that has any, by problem id.
+pub fn problem_notes(db: &Connection) -> rusqlite::Result<HashMap<i64, String>> {
+    let mut statement = db.prepare("SELECT id, notes FROM user_problems WHERE notes != ''")?;
+    let notes_iter = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    notes_iter.collect()
+}
+
+pub fn set_notes(db: &Connection, problem_id: i64, notes: &str) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE user_problems SET notes = ?1 WHERE id = ?2",
+        (notes, problem_id),
+    )?;
+    Ok(())
+}
+
+/// Every tag with how many problems use it, alphabetically.
+pub fn load_tags(db: &Connection) -> rusqlite::Result<Vec<Tag>> {
+    let mut statement = db.prepare(
+        "SELECT t.id, t.name, COUNT(pt.problem_id) FROM tags t
+         LEFT JOIN problem_tags pt ON pt.tag_id = t.id
+         GROUP BY t.id ORDER BY t.name COLLATE NOCASE",
+    )?;
+    let tag_iter = statement.query_map([], |row| {
+        Ok(Tag {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            problems: row.get(2)?,
+        })
+    })?;
+    tag_iter.collect()
+}
+
+/// Tag names per problem id, alphabetically.
+pub fn problem_tags(db: &Connection) -> rusqlite::Result<HashMap<i64, Vec<String>>> {
+    let mut statement = db.prepare(
+        "SELECT pt.problem_id, t.name FROM problem_tags pt JOIN tags t ON t.id = pt.tag_id
+         ORDER BY t.name COLLATE NOCASE",
+    )?;
+    let mut tags: HashMap<i64, Vec<String>> = HashMap::new();
+    let tag_iter = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    for tag_result in tag_iter {
+        let (problem_id, name) = tag_result?;
+        tags.entry(problem_id).or_default().push(name);
+    }
+    Ok(tags)
+}
+
+/// Replaces a problem's tags, creating any that don't exist yet. Names are
+/// normalised like problem names and matched ignoring case.
+pub fn set_problem_tags(
+    db: &Connection,
+    problem_id: i64,
+    names: &[String],
+) -> rusqlite::Result<()> {
+    db.execute(
+        "DELETE FROM problem_tags WHERE problem_id = ?1",
+        [problem_id],
+    )?;
+    add_problem_tags(db, problem_id, names)
+}
+
+/// Tags a problem with `names` on top of the tags it already has.
+pub fn add_problem_tags(
+    db: &Connection,
+    problem_id: i64,
+    names: &[String],
+) -> rusqlite::Result<()> {
+    for name in names {
+        let name = normalize_name(name);
+        if name.is_empty() {
+            continue;
+        }
+        db.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", [&name])?;
+        db.execute(
+            "INSERT OR IGNORE INTO problem_tags (problem_id, tag_id)
+             SELECT ?1, id FROM tags WHERE name = ?2",
+            (problem_id, &name),
+        )?;
+    }
+    Ok(())
+}
+
+/// Renames a tag. Returns false if the name is blank or another tag already
+/// has it.
+pub fn rename_tag(db: &Connection, tag_id: i64, name: &str) -> rusqlite::Result<bool> {
+    let name = normalize_name(name);
+    if name.is_empty() {
+        return Ok(false);
+    }
+    let taken: Option<i64> = db
+        .query_row("SELECT id FROM tags WHERE name = ?1", [&name], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    if taken.is_some_and(|id| id != tag_id) {
+        return Ok(false);
+    }
+    db.execute("UPDATE tags SET name = ?1 WHERE id = ?2", (&name, tag_id))?;
+    Ok(true)
+}
+
+/// Moves every problem tagged `from` over to `into` and removes `from`.
+pub fn merge_tags(db: &Connection, from: i64, into: i64) -> rusqlite::Result<()> {
+    if from == into {
+        return Ok(());
+    }
+    let transaction = db.unchecked_transaction()?;
+    transaction.execute(
+        "INSERT OR IGNORE INTO problem_tags (problem_id, tag_id)
+         SELECT problem_id, ?2 FROM problem_tags WHERE tag_id = ?1",
+        (from, into),
+    )?;
+    transaction.execute("DELETE FROM problem_tags WHERE tag_id = ?1", [from])?;
+    transaction.execute("DELETE FROM tags WHERE id = ?1", [from])?;
+    transaction.commit()
+}
+
+/// Setting that holds the id of the deck everything is scoped to.
+const DECK_SETTING: &str = "deck";
+
+/// Every deck with how many problems are in it, alphabetically.
+pub fn load_decks(db: &Connection) -> rusqlite::Result<Vec<Deck>> {
+    let mut statement = db.prepare(
+        "SELECT d.id, d.name, d.description, COUNT(dp.problem_id) FROM decks d
+         LEFT JOIN deck_problems dp ON dp.deck_id = d.id
+         GROUP BY d.id ORDER BY d.name COLLATE NOCASE",
+    )?;
+    let deck_iter = statement.query_map([], |row| {
+        Ok(Deck {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            problems: row.get(3)?,
+        })
+    })?;
+    deck_iter.collect()
+}
+
+/// Adds an empty deck and returns its id, or `None` if a deck already has
+/// that name, ignoring case.
+pub fn create_deck(
+    db: &Connection,
+    name: &str,
+    description: &str,
+) -> rusqlite::Result<Option<i64>> {
+    let added = db.execute(
+        "INSERT OR IGNORE INTO decks (name, description) VALUES (?1, ?2)",
+        (normalize_name(name), description.trim()),
+    )?;
+    Ok((added > 0).then(|| db.last_insert_rowid()))
+}
+
+/// Looks a deck up by name, ignoring case and stray whitespace.
+pub fn find_deck_id(db: &Connection, name: &str) -> rusqlite::Result<Option<i64>> {
+    db.query_row(
+        "SELECT id FROM decks WHERE name = ?1",
+        [normalize_name(name)],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+/// Renames a deck. Returns false if the name is blank or another deck already
+/// has it.
+pub fn rename_deck(db: &Connection, deck_id: i64, name: &str) -> rusqlite::Result<bool> {
+    let name = normalize_name(name);
+    if name.is_empty() {
+        return Ok(false);
+    }
+    let taken: Option<i64> = db
+        .query_row("SELECT id FROM decks WHERE name = ?1", [&name], |row| {
+            row.get(0)
+        })
+        .optional()?;
+    if taken.is_some_and(|id| id != deck_id) {
+        return Ok(false);
+    }
+    db.execute("UPDATE decks SET name = ?1 WHERE id = ?2", (&name, deck_id))?;
+    Ok(true)
+}
+
+pub fn set_deck_description(
+    db: &Connection,
+    deck_id: i64,
+    description: &str,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE decks SET description = ?1 WHERE id = ?2",
+        (description.trim(), deck_id),
+    )?;
+    Ok(())
+}
+
+/// Removes a deck, leaving its problems and their history alone. If it was
+/// the chosen deck, everything goes back to showing all problems.
+pub fn delete_deck(db: &Connection, deck_id: i64) -> rusqlite::Result<()> {
+    let transaction = db.unchecked_transaction()?;
+    transaction.execute("DELETE FROM deck_problems WHERE deck_id = ?1", [deck_id])?;
+    transaction.execute("DELETE FROM decks WHERE id = ?1", [deck_id])?;
+    transaction.execute(
+        "DELETE FROM settings WHERE key = ?1 AND value = ?2",
+        (DECK_SETTING, deck_id.to_string()),
+    )?;
+    transaction.commit()
+}
+
+/// Every problem's id and name, alphabetically, with whether it is in the
+/// deck.
+pub fn deck_candidates(
+    db: &Connection,
+    deck_id: i64,
+) -> rusqlite::Result<Vec<(i64, String, bool)>> {
+    let mut statement = db.prepare(
+        "SELECT p.id, p.problem_name, dp.problem_id IS NOT NULL FROM user_problems p
+         LEFT JOIN deck_problems dp ON dp.problem_id = p.id AND dp.deck_id = ?1
+         ORDER BY p.problem_name COLLATE NOCASE",
+    )?;
+    let candidate_iter =
+        statement.query_map([deck_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    candidate_iter.collect()
+}
+
+/// The problems in a deck, alphabetically.
+pub fn deck_problems(db: &Connection, deck_id: i64) -> rusqlite::Result<Vec<Problem>> {
+    let mut statement = db.prepare(&format!(
+        "SELECT {PROBLEM_COLUMNS} FROM user_problems
+         WHERE id IN (SELECT problem_id FROM deck_problems WHERE deck_id = ?1)
+         ORDER BY problem_name COLLATE NOCASE"
+    ))?;
+    let problem_iter = statement.query_map([deck_id], problem_from_row)?;
+    problem_iter.collect()
+}
+
+/// Adds a problem to a deck or takes it out.
+pub fn set_deck_member(
+    db: &Connection,
+    deck_id: i64,
+    problem_id: i64,
+    member: bool,
+) -> rusqlite::Result<()> {
+    if member {
+        db.execute(
+            "INSERT OR IGNORE INTO deck_problems (deck_id, problem_id) VALUES (?1, ?2)",
+            (deck_id, problem_id),
+        )?;
+    } else {
+        db.execute(
+            "DELETE FROM deck_problems WHERE deck_id = ?1 AND problem_id = ?2",
+            (deck_id, problem_id),
+        )?;
+    }
+    Ok(())
+}
+
+/// The deck the queues, the problem list and the graph are scoped to, if one
+/// is chosen and still exists.
+pub fn current_deck(db: &Connection) -> rusqlite::Result<Option<Deck>> {
+    let Some(deck_id) = get_setting(db, DECK_SETTING)?.and_then(|value| value.parse::<i64>().ok())
+    else {
+        return Ok(None);
+    };
+    Ok(load_decks(db)?.into_iter().find(|deck| deck.id == deck_id))
+}
+
+/// Scopes everything to a deck, or to all problems again with `None`.
+pub fn set_current_deck(db: &Connection, deck_id: Option<i64>) -> rusqlite::Result<()> {
+    match deck_id {
+        Some(deck_id) => set_setting(db, DECK_SETTING, &deck_id.to_string()),
+        None => {
+            db.execute("DELETE FROM settings WHERE key = ?1", [DECK_SETTING])?;
+            Ok(())
+        }
+    }
+}
+
+/// Ids of the problems in the chosen deck, or `None` when no deck is chosen
+/// and every problem is in scope.
+pub fn deck_scope(db: &Connection) -> rusqlite::Result<Option<HashSet<i64>>> {
+    let Some(deck) = current_deck(db)? else {
+        return Ok(None);
+    };
+    let mut statement = db.prepare("SELECT problem_id FROM deck_problems WHERE deck_id = ?1")?;
+    let member_iter = statement.query_map([deck.id], |row| row.get(0))?;
+    member_iter.collect::<rusqlite::Result<_>>().map(Some)
+}
+
+/// Whether a problem falls within a [`deck_scope`].
+pub fn in_scope(scope: &Option<HashSet<i64>>, problem_id: i64) -> bool {
+    scope
+        .as_ref()
+        .is_none_or(|members| members.contains(&problem_id))
+}
+
+/// Links `problem_id` to `related_id`, read as "problem is similar to / a
+/// follow-up of / a prerequisite of related". Similar links go both ways, so
+/// they are stored once whichever problem they were made from. Returns false
+/// if the link was already there, and fails for a link to the problem itself.
+pub fn link_problems(
+    db: &Connection,
+    problem_id: i64,
+    related_id: i64,
+    kind: RelationKind,
+) -> rusqlite::Result<bool> {
+    let (problem_id, related_id) = if kind == RelationKind::Similar {
+        (problem_id.min(related_id), problem_id.max(related_id))
+    } else {
+        (problem_id, related_id)
+    };
+    let inserted = db.execute(
+        "INSERT INTO problem_relations (problem_id, related_id, kind)
+         VALUES (?1, ?2, ?3) ON CONFLICT DO NOTHING",
+        (problem_id, related_id, kind.as_str()),
+    )?;
+    Ok(inserted > 0)
+}
+
+/// Removes every link between two problems, in either direction. Returns how
+/// many there were.
+pub fn unlink_problems(
+    db: &Connection,
+    problem_id: i64,
+    related_id: i64,
+) -> rusqlite::Result<usize> {
+    db.execute(
+        "DELETE FROM problem_relations
+         WHERE (problem_id = ?1 AND related_id = ?2) OR (problem_id = ?2 AND related_id = ?1)",
+        (problem_id, related_id),
+    )
+}
+
+/// Linked problems per problem id, from both ends of each link, ordered by
+/// kind and then name.
+pub fn problem_relations(db: &Connection) -> rusqlite::Result<HashMap<i64, Vec<Relation>>> {
+    let mut statement = db.prepare(
+        "SELECT r.problem_id, a.problem_name, r.related_id, b.problem_name, r.kind
+         FROM problem_relations r
+         JOIN user_problems a ON a.id = r.problem_id
+         JOIN user_problems b ON b.id = r.related_id",
+    )?;
+    type RelationRow = (i64, String, i64, String, String);
+    let rows: Vec<RelationRow> = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    let mut relations: HashMap<i64, Vec<Relation>> = HashMap::new();
+    for (problem_id, problem_name, related_id, related_name, kind) in rows {
+        let Some(kind) = RelationKind::parse(&kind) else {
+            continue;
+        };
+        relations.entry(problem_id).or_default().push(Relation {
+            kind,
+            incoming: false,
+            problem_id: related_id,
+            problem_name: related_name,
+        });
+        relations.entry(related_id).or_default().push(Relation {
+            kind,
+            incoming: true,
+            problem_id,
+            problem_name,
+        });
+    }
+    for linked in relations.values_mut() {
+        linked.sort_by(|a, b| {
+            (a.kind, a.incoming, a.problem_name.to_lowercase()).cmp(&(
+                b.kind,
+                b.incoming,
+                b.problem_name.to_lowercase(),
+            ))
+        });
+    }
+    Ok(relations)
+}
+
+/// Gives a problem a new name. Returns false if another problem already has
+/// that name, ignoring case.
+pub fn rename_problem(
+    db: &Connection,
+    problem_id: i64,
+    problem_name: &str,
+) -> rusqlite::Result<bool> {
+    let problem_name = normalize_name(problem_name);
+    if find_problem_id(db, &problem_name)?.is_some_and(|id| id != problem_id) {
+        return Ok(false);
+    }
+    db.execute(
+        "UPDATE user_problems SET problem_name = ?1 WHERE id = ?2",
+        (problem_name, problem_id),
+    )?;
+    Ok(true)
+}
+
+/// When a problem is next due. Rows from before scheduling existed have no due
+/// date and count as due since their last entry.
+pub fn due_date_of(problem: &Problem) -> Option<NaiveDate> {
+    problem
+        .due_date
+        .or(problem.state.last_review)
+        .map(|due| due.date_naive())
+}
+
+/// Every problem still in review, i.e. neither mastered nor suspended.
+pub fn active_problems(db: &Connection) -> rusqlite::Result<Vec<Problem>> {
+    let mut statement = db.prepare(&format!(
+        "SELECT {PROBLEM_COLUMNS} FROM user_problems WHERE mastered = 0 AND suspended = 0"
+    ))?;
+    let problem_iter = statement.query_map([], problem_from_row)?;
+    problem_iter.collect()
+}
+
+/// Problems still in review whose due date is `today` or earlier, most overdue
+/// first, each paired with how many days overdue it is.
+pub fn due_problems(db: &Connection, today: NaiveDate) -> rusqlite::Result<Vec<(Problem, i64)>> {
+    let mut due = vec![];
+    for problem in active_problems(db)? {
+        if let Some(due_date) = due_date_of(&problem)
+            && due_date <= today
+        {
+            due.push((problem, (today - due_date).num_days()));
+        }
+    }
+    due.sort_by(|(a, a_overdue), (b, b_overdue)| {
+        b_overdue.cmp(a_overdue).then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(due)
+}
+
+/// Returns a mastered problem to the review queues with a fresh streak.
+pub fn unmaster_problem(db: &Connection, problem_id: i64) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE user_problems SET mastered = 0, top_streak = 0 WHERE id = ?1",
+        [problem_id],
+    )?;
+    Ok(())
+}
+
+/// Parks a problem out of the queues, or brings it back, without touching its
+/// schedule or history.
+pub fn set_suspended(db: &Connection, problem_id: i64, suspended: bool) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE user_problems SET suspended = ?1 WHERE id = ?2",
+        (suspended, problem_id),
+    )?;
+    Ok(())
+}
+
+/// Appends one attempt to the problem's history. Earlier attempts are never
+/// changed; `user_problems` only keeps the state they add up to.
+pub fn record_review(
+    db: &Connection,
+    problem_id: i64,
+    rating: Rating,
+    reviewed_at: &DateTime<Utc>,
+    duration_secs: Option<i64>,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT INTO reviews (problem_id, rating, reviewed_at, duration_secs)
+         VALUES (?1, ?2, ?3, ?4)",
+        (problem_id, rating, timestamp(reviewed_at), duration_secs),
+    )?;
+    Ok(())
+}
+
+/// Logs a snooze or pin next to the reviews, so it can be told apart from a
+/// real rating.
+pub fn record_due_override(
+    db: &Connection,
+    problem_id: i64,
+    kind: &str,
+    previous_due: Option<&DateTime<Utc>>,
+    new_due: &DateTime<Utc>,
+    created_at: &DateTime<Utc>,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT INTO due_overrides (problem_id, kind, previous_due, new_due, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        (
+            problem_id,
+            kind,
+            previous_due.map(timestamp),
+            timestamp(new_due),
+            timestamp(created_at),
+        ),
+    )?;
+    Ok(())
+}
+
+/// Every stored review, grouped by problem and in the order they happened.
+pub fn load_reviews(db: &Connection) -> rusqlite::Result<Vec<Review>> {
+    let mut statement = db.prepare(
+        "SELECT problem_id, rating, reviewed_at FROM reviews ORDER BY problem_id, reviewed_at",
+    )?;
+    let review_iter = statement.query_map([], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, Rating>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+
+    let mut reviews = vec![];
+    for review_result in review_iter {
+        let (problem_id, rating, reviewed_at) = review_result?;
+        if let Some(reviewed_at) = parse_timestamp(&reviewed_at) {
+            reviews.push(Review {
+                problem_id,
+                rating,
+                reviewed_at,
+            });
+        }
+    }
+    Ok(reviews)
+}
+
+/// Attaches code to the problem's most recent attempt.
+pub fn set_solution(
+    db: &Connection,
+    problem_id: i64,
+    language: Option<&str>,
+    code: &str,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE reviews SET language = ?1, solution = ?2
+         WHERE rowid = (SELECT rowid FROM reviews WHERE problem_id = ?3
+                        ORDER BY reviewed_at DESC, rowid DESC LIMIT 1)",
+        (language, code, problem_id),
+    )?;
+    Ok(())
+}
+
+/// The attempts at a problem that have code attached, oldest first.
+pub fn load_attempts(db: &Connection, problem_id: i64) -> rusqlite::Result<Vec<Attempt>> {
+    let mut statement = db.prepare(
+        "SELECT rating, reviewed_at, duration_secs, language, solution FROM reviews
+         WHERE problem_id = ?1 AND solution IS NOT NULL ORDER BY reviewed_at, rowid",
+    )?;
+    let attempt_iter = statement.query_map([problem_id], |row| {
+        Ok((
+            row.get::<_, Rating>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, Option<i64>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+            row.get::<_, String>(4)?,
+        ))
+    })?;
+
+    let mut attempts = vec![];
+    for attempt_result in attempt_iter {
+        let (rating, reviewed_at, duration_secs, language, code) = attempt_result?;
+        if let Some(reviewed_at) = parse_timestamp(&reviewed_at) {
+            attempts.push(Attempt {
+                rating,
+                reviewed_at,
+                duration_secs,
+                language,
+                code,
+            });
+        }
+    }
+    Ok(attempts)
+}
+
+/// Solve times of every problem timed at least once, oldest attempt first.
+pub fn solve_times(db: &Connection) -> rusqlite::Result<HashMap<i64, Vec<i64>>> {
+    let mut statement = db.prepare(
+        "SELECT problem_id, duration_secs FROM reviews WHERE duration_secs IS NOT NULL
+         ORDER BY problem_id, reviewed_at",
+    )?;
+    let mut times: HashMap<i64, Vec<i64>> = HashMap::new();
+    let time_iter = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    for time_result in time_iter {
+        let (problem_id, seconds) = time_result?;
+        times.entry(problem_id).or_default().push(seconds);
+    }
+    Ok(times)
+}
+
+/// How a problem's last rating reads in lists: problems added from a deck
+/// file and not attempted yet have none.
+pub fn rating_label(review_counts: &HashMap<i64, i64>, problem: &Problem) -> String {
+    if review_counts.contains_key(&problem.id) {
+        problem.rating.to_string()
+    } else {
+        "unrated".to_string()
+    }
+}
+
+/// How many reviews are stored for each problem.
+pub fn review_counts(db: &Connection) -> rusqlite::Result<HashMap<i64, i64>> {
+    let mut statement =
+        db.prepare("SELECT problem_id, COUNT(*) FROM reviews GROUP BY problem_id")?;
+    let count_iter = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    count_iter.collect()
+}
+
+/// Problems added without a rating, such as deck imports.
+pub fn added_unrated(db: &Connection) -> rusqlite::Result<HashSet<i64>> {
+    let mut statement = db.prepare("SELECT id FROM user_problems WHERE added_unrated = 1")?;
+    let id_iter = statement.query_map([], |row| row.get(0))?;
+    id_iter.collect()
+}
+
+/// Every declared pause, earliest first.
+pub fn load_pauses(db: &Connection) -> rusqlite::Result<Vec<Pause>> {
+    let mut statement =
+        db.prepare("SELECT start_date, end_date FROM pauses ORDER BY start_date")?;
+    let pause_iter = statement.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?;
+
+    let mut pauses = vec![];
+    for pause_result in pause_iter {
+        let (start, end) = pause_result?;
+        if let (Ok(start), Ok(end)) = (start.parse::<NaiveDate>(), end.parse::<NaiveDate>()) {
+            pauses.push(Pause { start, end });
+        }
+    }
+    Ok(pauses)
+}
+
+pub fn insert_pause(db: &Connection, pause: &Pause) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT INTO pauses (start_date, end_date) VALUES (?1, ?2)",
+        (pause.start.to_string(), pause.end.to_string()),
+    )?;
+    Ok(())
+}
+
+/// Removes the pause starting on `start`; returns whether there was one.
+pub fn delete_pause(db: &Connection, start: NaiveDate) -> rusqlite::Result<bool> {
+    let deleted = db.execute(
+        "DELETE FROM pauses WHERE start_date = ?1",
+        [start.to_string()],
+    )?;
+    Ok(deleted > 0)
+}
+
+pub fn set_due_date(
+    db: &Connection,
+    problem_id: i64,
+    due_date: &DateTime<Utc>,
+) -> rusqlite::Result<()> {
+    db.execute(
+        "UPDATE user_problems SET due_date = ?1 WHERE id = ?2",
+        (timestamp(due_date), problem_id),
+    )?;
+    Ok(())
+}
+
+/// Reads an on/off setting, stored as on/off, true/false or 1/0.
+pub fn parse_enabled(value: &str) -> Option<bool> {
+    match value.trim() {
+        "on" | "true" | "1" => Some(true),
+        "off" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// An on/off setting, or `None` if it is unset or unreadable.
+pub fn get_bool_setting(db: &Connection, key: &str) -> rusqlite::Result<Option<bool>> {
+    Ok(get_setting(db, key)?.as_deref().and_then(parse_enabled))
+}
+
+pub fn get_setting(db: &Connection, key: &str) -> rusqlite::Result<Option<String>> {
+    db.query_row("SELECT value FROM settings WHERE key = ?1", [key], |row| {
+        row.get(0)
+    })
+    .optional()
+}
+
+pub fn set_setting(db: &Connection, key: &str, value: &str) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        (key, value),
+    )?;
+    Ok(())
+}
+
+pub fn update_problem(
+    db: &Connection,
+    problem_id: i64,
+    rating: Rating,
+    entry_date: &DateTime<Utc>,
+    duration_secs: Option<i64>,
+) -> rusqlite::Result<bool> {
+    // One transaction, so a problem is never left rescheduled without the
+    // review that moved it.
+    let transaction = db.unchecked_transaction()?;
+    if let Some(previous) = load_problem(&transaction, problem_id)? {
+        let (state, _) =
+            scheduler::load(&transaction)?.schedule(&previous.state, rating, *entry_date);
+        let (state, due_date) = LoadBalancer::load(&transaction)?.apply(
+            &transaction,
+            problem_id,
+            state,
+            *entry_date,
+        )?;
+        let due_date = pause::shift_past_pauses(&load_pauses(&transaction)?, due_date);
+        let (top_streak, mastered) =
+            GraduationRule::load(&transaction)?.apply(previous.top_streak, rating);
+        let problem = Problem {
+            id: problem_id,
+            name: previous.name,
+            rating,
+            entry_date: *entry_date,
+            due_date: Some(due_date),
+            state,
+            top_streak,
+            mastered,
+            suspended: previous.suspended,
+            metadata: previous.metadata,
+        };
+
+        transaction.execute(
+            "UPDATE user_problems SET problem_rating = ?1, entry_date = ?2, due_date = ?3,
+                ease_factor = ?4, interval_days = ?5, repetitions = ?6,
+                stability = ?7, difficulty = ?8, leitner_box = ?9,
+                top_streak = ?10, mastered = ?11
+             WHERE id = ?12",
+            (
+                problem.rating,
+                timestamp(&problem.entry_date),
+                problem.due_date.as_ref().map(timestamp),
+                problem.state.ease_factor,
+                problem.state.interval,
+                problem.state.repetitions,
+                problem.state.stability,
+                problem.state.difficulty,
+                problem.state.leitner_box,
+                problem.top_streak,
+                problem.mastered,
+                problem.id,
+            ),
+        )?;
+        record_review(&transaction, problem_id, rating, entry_date, duration_secs)?;
+        transaction.commit()?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Adds a problem that hasn't been attempted yet, such as one from a shared
+/// deck, and returns its id. It has no reviews, so it counts as new and is due
+/// from `now`; the stored rating is only a placeholder until the first one.
+pub fn insert_unrated_problem(
+    db: &Connection,
+    problem_name: &str,
+    metadata: &ProblemMetadata,
+    now: &DateTime<Utc>,
+) -> rusqlite::Result<i64> {
+    db.execute(
+        "INSERT INTO user_problems (problem_name, problem_rating, entry_date, due_date,
+            added_unrated)
+         VALUES (?1, ?2, ?3, ?3, 1)",
+        (
+            normalize_name(problem_name),
+            Rating::Blackout,
+            timestamp(now),
+        ),
+    )?;
+    let problem_id = db.last_insert_rowid();
+    set_metadata(db, problem_id, metadata)?;
+    Ok(problem_id)
+}
+
+pub fn insert_new_problem(
+    db: &Connection,
+    problem_name: &str,
+    rating: Rating,
+    entry_date: &DateTime<Utc>,
+    duration_secs: Option<i64>,
+) -> rusqlite::Result<bool> {
+    // One transaction, so a problem is never stored without its balanced due
+    // date and first review.
+    let transaction = db.unchecked_transaction()?;
+    let problem_name = &normalize_name(problem_name);
+    // Check if row exists
+    if !check_row_exists(&transaction, problem_name)? {
+        let (state, _) =
+            scheduler::load(&transaction)?.schedule(&ScheduleState::default(), rating, *entry_date);
+        let (top_streak, mastered) = GraduationRule::load(&transaction)?.apply(0, rating);
+        transaction.execute(
+            "INSERT INTO user_problems (problem_name, problem_rating, entry_date,
+                ease_factor, interval_days, repetitions, stability, difficulty, leitner_box,
+                top_streak, mastered)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            (
+                problem_name,
+                rating,
+                timestamp(entry_date),
+                state.ease_factor,
+                state.interval,
+                state.repetitions,
+                state.stability,
+                state.difficulty,
+                state.leitner_box,
+                top_streak,
+                mastered,
+            ),
+        )?;
+        let problem_id = transaction.last_insert_rowid();
+        // Balanced once stored, so the new problem has an id to be left out
+        // of the load counts by.
+        let (state, due_date) = LoadBalancer::load(&transaction)?.apply(
+            &transaction,
+            problem_id,
+            state,
+            *entry_date,
+        )?;
+        let due_date = pause::shift_past_pauses(&load_pauses(&transaction)?, due_date);
+        transaction.execute(
+            "UPDATE user_problems SET due_date = ?1, interval_days = ?2 WHERE id = ?3",
+            (timestamp(&due_date), state.interval, problem_id),
+        )?;
+        record_review(&transaction, problem_id, rating, entry_date, duration_secs)?;
+        transaction.commit()?;
+        return Ok(true); // Signifies a new row was added
+    }
+
+    Ok(false) // Signifies nothing was added, but no error occurred
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+
+    fn database() -> Connection {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        db
+    }
+
+    fn add(db: &Connection, name: &str) -> i64 {
+        insert_unrated_problem(db, name, &ProblemMetadata::default(), &Utc::now()).unwrap()
+    }
+
+    /// Each problem's links as (label, linked problem name).
+    fn labels(db: &Connection) -> HashMap<i64, Vec<(&'static str, String)>> {
+        problem_relations(db)
+            .unwrap()
+            .into_iter()
+            .map(|(id, linked)| {
+                let linked = linked
+                    .into_iter()
+                    .map(|relation| (relation.label(), relation.problem_name))
+                    .collect();
+                (id, linked)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn links_read_from_both_ends() {
+        let db = database();
+        let container = add(&db, "Container With Most Water");
+        let trapping = add(&db, "Trapping Rain Water");
+        let two_sum = add(&db, "Two Sum");
+        assert!(link_problems(&db, trapping, container, RelationKind::FollowUp).unwrap());
+        assert!(link_problems(&db, two_sum, container, RelationKind::Similar).unwrap());
+
+        let labels = labels(&db);
+        assert_eq!(
+            labels[&container],
+            [
+                ("Similar to", "Two Sum".to_string()),
+                ("Followed up by", "Trapping Rain Water".to_string()),
+            ]
+        );
+        assert_eq!(
+            labels[&trapping],
+            [("Follow-up of", "Container With Most Water".to_string())]
+        );
+        assert_eq!(
+            labels[&two_sum],
+            [("Similar to", "Container With Most Water".to_string())]
+        );
+    }
+
+    #[test]
+    fn linking_again_changes_nothing() {
+        let db = database();
+        let a = add(&db, "A");
+        let b = add(&db, "B");
+        assert!(link_problems(&db, a, b, RelationKind::Similar).unwrap());
+        assert!(!link_problems(&db, a, b, RelationKind::Similar).unwrap());
+        assert!(!link_problems(&db, b, a, RelationKind::Similar).unwrap());
+        assert!(link_problems(&db, a, b, RelationKind::Prerequisite).unwrap());
+        assert!(!link_problems(&db, a, b, RelationKind::Prerequisite).unwrap());
+        assert_eq!(problem_relations(&db).unwrap()[&a].len(), 2);
+        assert_eq!(unlink_problems(&db, b, a).unwrap(), 2);
+        assert!(problem_relations(&db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn a_problem_cannot_be_linked_to_itself() {
+        let db = database();
+        let a = add(&db, "A");
+        for kind in [
+            RelationKind::Similar,
+            RelationKind::FollowUp,
+            RelationKind::Prerequisite,
+        ] {
+            assert!(link_problems(&db, a, a, kind).is_err());
+        }
+        assert!(problem_relations(&db).unwrap().is_empty());
+    }
+
+    #[test]
+    fn deleting_a_problem_removes_its_links() {
+        let db = database();
+        let a = add(&db, "A");
+        let b = add(&db, "B");
+        let c = add(&db, "C");
+        link_problems(&db, a, b, RelationKind::FollowUp).unwrap();
+        link_problems(&db, c, a, RelationKind::Similar).unwrap();
+        link_problems(&db, b, c, RelationKind::Prerequisite).unwrap();
+
+        db.execute("DELETE FROM user_problems WHERE id = ?1", [a])
+            .unwrap();
+        let links: i64 = db
+            .query_row("SELECT COUNT(*) FROM problem_relations", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(links, 1);
+        assert_eq!(labels(&db)[&b], [("Prerequisite of", "C".to_string())]);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::db;
+use crate::lib::{Deck, DeckFile, DeckFileProblem, DeckImport, Difficulty, ProblemMetadata};
+use chrono::Utc;
+use rusqlite::Connection;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+/// Reads a deck file, as JSON if its name ends in `.json` and as TOML
+/// otherwise.
+pub fn read(path: &Path) -> Result<DeckFile, Box<dyn Error>> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("could not read {}: {error}", path.display()))?;
+    let deck: DeckFile = if is_json(path) {
+        serde_json::from_str(&text)?
+    } else {
+        toml::from_str(&text)?
+    };
+    if db::normalize_name(&deck.name).is_empty() {
+        return Err(format!("{} has no deck name", path.display()).into());
+    }
+    Ok(deck)
+}
+
+/// Writes a deck file, picking the format from the name like [`read`].
+pub fn write(path: &Path, deck: &DeckFile) -> Result<(), Box<dyn Error>> {
+    let text = if is_json(path) {
+        serde_json::to_string_pretty(deck)?
+    } else {
+        toml::to_string_pretty(deck)?
+    };
+    fs::write(path, text)?;
+    Ok(())
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Merges a deck file into the database, into the deck of the same name if
+/// there is one. Problems are matched by name the way the add form matches
+/// them, so ones already there keep their progress and only gain the details
+/// and tags they were missing. The rest are added unrated and due now, to be
+/// introduced under the daily limit for new problems.
+pub fn import(db: &Connection, deck: &DeckFile) -> rusqlite::Result<DeckImport> {
+    let transaction = db.unchecked_transaction()?;
+    let mut summary = DeckImport::default();
+    let deck_id = match db::create_deck(&transaction, &deck.name, &deck.description)? {
+        Some(deck_id) => {
+            summary.deck_created = true;
+            deck_id
+        }
+        None => db::find_deck_id(&transaction, &deck.name)?
+            .ok_or(rusqlite::Error::QueryReturnedNoRows)?,
+    };
+
+    let now = Utc::now();
+    for problem in &deck.problems {
+        let name = db::normalize_name(&problem.name);
+        if name.is_empty() {
+            summary
+                .warnings
+                .push("a problem without a name was left out".to_string());
+            continue;
+        }
+        let metadata = metadata_of(problem, &name, &mut summary.warnings);
+        let problem_id = match db::find_problem_id(&transaction, &name)? {
+            Some(problem_id) => {
+                summary.existing += 1;
+                db::fill_metadata(&transaction, problem_id, &metadata)?;
+                problem_id
+            }
+            None => {
+                summary.added += 1;
+                db::insert_unrated_problem(&transaction, &name, &metadata, &now)?
+            }
+        };
+        db::add_problem_tags(&transaction, problem_id, &problem.tags)?;
+        db::set_deck_member(&transaction, deck_id, problem_id, true)?;
+    }
+    transaction.commit()?;
+    Ok(summary)
+}
+
+/// The details of a deck file entry worth storing; blank fields count as
+/// missing.
+fn metadata_of(
+    problem: &DeckFileProblem,
+    name: &str,
+    warnings: &mut Vec<String>,
+) -> ProblemMetadata {
+    let present = |field: &Option<String>| {
+        field
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let difficulty = present(&problem.difficulty).and_then(|label| {
+        let difficulty = Difficulty::parse(&label);
+        if difficulty.is_none() {
+            warnings.push(format!(
+                "{name}: unknown difficulty \"{label}\" was left out"
+            ));
+        }
+        difficulty
+    });
+    ProblemMetadata {
+        url: present(&problem.url),
+        platform: present(&problem.platform),
+        number: present(&problem.number),
+        difficulty,
+    }
+}
+
+/// A deck as a file to share: its problems' names, details and tags, but no
+/// ratings, notes, code or schedule.
+pub fn export(db: &Connection, deck: &Deck) -> rusqlite::Result<DeckFile> {
+    let mut tags = db::problem_tags(db)?;
+    let problems = db::deck_problems(db, deck.id)?
+        .into_iter()
+        .map(|problem| DeckFileProblem {
+            tags: tags.remove(&problem.id).unwrap_or_default(),
+            name: problem.name,
+            url: problem.metadata.url,
+            platform: problem.metadata.platform,
+            number: problem.metadata.number,
+            difficulty: problem
+                .metadata
+                .difficulty
+                .map(|difficulty| difficulty.as_str().to_string()),
+        })
+        .collect();
+    Ok(DeckFile {
+        name: deck.name.clone(),
+        description: deck.description.clone(),
+        problems,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Rating;
+    use crate::migrations;
+
+    fn database() -> Connection {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        db
+    }
+
+    /// A deck of two problems, one with every detail filled in.
+    fn shared_deck() -> DeckFile {
+        let db = database();
+        let deck_id = db::create_deck(&db, "Graphs", "BFS and DFS")
+            .unwrap()
+            .unwrap();
+        let metadata = ProblemMetadata {
+            url: Some("https://leetcode.com/problems/number-of-islands/".to_string()),
+            platform: Some("LeetCode".to_string()),
+            number: Some("200".to_string()),
+            difficulty: Some(Difficulty::Medium),
+        };
+        for (name, metadata) in [
+            ("Number of Islands", metadata),
+            ("Course Schedule", ProblemMetadata::default()),
+        ] {
+            let id = db::insert_unrated_problem(&db, name, &metadata, &Utc::now()).unwrap();
+            db::add_problem_tags(&db, id, &["graphs".to_string()]).unwrap();
+            db::set_deck_member(&db, deck_id, id, true).unwrap();
+        }
+        let deck = db::load_decks(&db)
+            .unwrap()
+            .into_iter()
+            .find(|deck| deck.id == deck_id)
+            .unwrap();
+        export(&db, &deck).unwrap()
+    }
+
+    #[test]
+    fn a_deck_survives_writing_and_reading() {
+        for extension in ["toml", "json"] {
+            let path =
+                std::env::temp_dir().join(format!("srl_r_deck_{}.{extension}", std::process::id()));
+            write(&path, &shared_deck()).unwrap();
+            let deck = read(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(deck.name, "Graphs");
+            assert_eq!(deck.description, "BFS and DFS");
+            let names: Vec<&str> = deck.problems.iter().map(|p| p.name.as_str()).collect();
+            assert_eq!(names, ["Course Schedule", "Number of Islands"]);
+            let islands = &deck.problems[1];
+            assert_eq!(islands.number.as_deref(), Some("200"));
+            assert_eq!(islands.difficulty.as_deref(), Some("Medium"));
+            assert_eq!(islands.tags, ["graphs"]);
+        }
+    }
+
+    #[test]
+    fn importing_merges_into_problems_already_there() {
+        let db = database();
+        db::insert_new_problem(
+            &db,
+            "number of islands",
+            Rating::Hesitant,
+            &Utc::now(),
+            None,
+        )
+        .unwrap();
+        let existing = db::find_problem_id(&db, "Number of Islands")
+            .unwrap()
+            .unwrap();
+
+        let mut deck = shared_deck();
+        deck.problems.push(DeckFileProblem {
+            name: "  ".to_string(),
+            url: None,
+            platform: None,
+            number: None,
+            difficulty: None,
+            tags: vec![],
+        });
+        deck.problems.push(DeckFileProblem {
+            name: "Word Ladder".to_string(),
+            url: None,
+            platform: None,
+            number: None,
+            difficulty: Some("Impossible".to_string()),
+            tags: vec![],
+        });
+
+        let summary = import(&db, &deck).unwrap();
+        assert!(summary.deck_created);
+        assert_eq!(summary.existing, 1);
+        assert_eq!(summary.added, 2);
+        assert_eq!(
+            summary.warnings,
+            [
+                "a problem without a name was left out",
+                "Word Ladder: unknown difficulty \"Impossible\" was left out",
+            ]
+        );
+
+        // The problem already there keeps its name and history and gains the
+        // details it was missing.
+        let problem = db::load_problem(&db, existing).unwrap().unwrap();
+        assert_eq!(problem.name, "number of islands");
+        assert_eq!(problem.metadata.number.as_deref(), Some("200"));
+        assert_eq!(db::review_counts(&db).unwrap().get(&existing), Some(&1));
+        assert_eq!(db::problem_tags(&db).unwrap()[&existing], ["graphs"]);
+
+        let summary = import(&db, &deck).unwrap();
+        assert!(!summary.deck_created);
+        assert_eq!((summary.existing, summary.added), (3, 0));
+    }
+}
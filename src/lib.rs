@@ -1,19 +1,30 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use crossterm::event::KeyEvent;
-use crossterm::event::{self, Event, KeyEventKind};
 use ratatui::Frame;
+use ratatui::text::Line;
 use ratatui::widgets::{ListState, Row, TableState};
-use std::io;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tui_textarea::TextArea;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Screen<'a> {
     HomeScreen(HomeScreen),
     MenuScreen(MenuScreen),
-    AddProblemScreen(AddProblemScreen<'a>),
+    AddProblemScreen(Box<AddProblemScreen<'a>>),
     ViewAllProblemsScreen(ViewAllProblemsScreen<'a>),
     GraphScreen(GraphScreen),
+    DueQueueScreen(DueQueueScreen<'a>),
+    ReviewSessionScreen(ReviewSessionScreen),
+    ForecastScreen(ForecastScreen),
+    SimulatorScreen(SimulatorScreen<'a>),
+    TagScreen(TagScreen<'a>),
+    NotesScreen(Box<NotesScreen<'a>>),
+    SolutionScreen(SolutionScreen),
+    DeckScreen(DeckScreen<'a>),
 }
 
 impl<'a> Default for Screen<'a> {
@@ -27,50 +38,457 @@ pub struct HomeScreen {}
 
 #[derive(Debug)]
 pub struct MenuScreen {
+    pub db: Arc<rusqlite::Connection>,
     pub menu_state: ListState,
     pub menu_options: &'static [&'static str],
+    /// Decks to pick from with Left and Right.
+    pub decks: Vec<Deck>,
+    /// The deck everything is scoped to; `None` shows all problems.
+    pub deck: Option<Deck>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub enum InputSelector {
     #[default]
     ProblemName,
     ProblemRating,
+    Platform,
+    Number,
+    Difficulty,
+    Url,
+    Tags,
+    Language,
+    Solution,
 }
 
 #[derive(Debug)]
 pub struct AddProblemScreen<'a> {
     pub problem_name: TextArea<'a>,
     pub problem_rating: TextArea<'a>,
-    pub entry_date: String,
+    pub problem_platform: TextArea<'a>,
+    pub problem_number: TextArea<'a>,
+    pub problem_difficulty: TextArea<'a>,
+    pub problem_url: TextArea<'a>,
+    /// Comma-separated tags, completed from `known_tags`.
+    pub problem_tags: TextArea<'a>,
+    pub known_tags: Vec<String>,
+    pub problem_language: TextArea<'a>,
+    /// Code written for this attempt, stored with the review it records.
+    pub problem_solution: TextArea<'a>,
     pub input_mode: InputSelector,
     pub db: Arc<rusqlite::Connection>,
     pub confirm_popup: bool,
     pub successful_problem_added: bool,
     pub failed_to_add_problem: bool,
     pub sucessfully_updated_problem: bool,
+    /// Why the last add or update failed, shown in place of the generic
+    /// failure line.
+    pub message: Option<String>,
     pub incorrect_rating: bool,
     pub incorrect_name: bool,
+    pub incorrect_difficulty: bool,
+    /// Times the attempt being entered.
+    pub stopwatch: Stopwatch,
 }
 
 #[derive(Debug)]
 pub struct Problem {
+    pub id: i64,
+    pub name: String,
+    pub rating: Rating,
+    pub entry_date: DateTime<Utc>,
+    /// Unset for rows from before scheduling existed; they count as due since
+    /// their last entry.
+    pub due_date: Option<DateTime<Utc>>,
+    pub state: ScheduleState,
+    /// Consecutive ratings at or above the graduation threshold.
+    pub top_streak: i64,
+    pub mastered: bool,
+    /// Parked by hand: kept with its history but left out of every queue.
+    pub suspended: bool,
+    pub metadata: ProblemMetadata,
+}
+
+/// How an attempt went, on SM-2's 1-5 scale. Stored as the number, and
+/// anything below [`Rating::Difficult`] counts as a lapse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
+    /// Couldn't solve it at all.
+    Blackout = 1,
+    /// Got it wrong, but the solution looked familiar.
+    Incorrect = 2,
+    /// Solved, with serious difficulty.
+    Difficult = 3,
+    /// Solved after some hesitation.
+    Hesitant = 4,
+    /// Solved cleanly.
+    Perfect = 5,
+}
+
+/// Optional details for finding a problem again and balancing practice.
+#[derive(Debug, Clone, Default)]
+pub struct ProblemMetadata {
+    pub url: Option<String>,
+    /// Where the problem comes from: LeetCode, NeetCode, Codeforces, ...
+    pub platform: Option<String>,
+    /// The platform's number or slug for the problem.
+    pub number: Option<String>,
+    pub difficulty: Option<Difficulty>,
+}
+
+/// A topic or pattern, such as "sliding window", that problems can share.
+#[derive(Debug, Clone)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    /// How many problems carry the tag.
+    pub problems: i64,
+}
+
+/// A named collection of problems, such as a curated study list.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    pub id: i64,
     pub name: String,
-    pub rating: String,
-    pub entry_date: String,
+    pub description: String,
+    /// How many problems are in the deck.
+    pub problems: i64,
+}
+
+/// A deck as shared between people: the problems and where to find them, but
+/// nobody's progress. Read from and written to TOML or JSON.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeckFile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default)]
+    pub problems: Vec<DeckFileProblem>,
+}
+
+/// One problem in a [`DeckFile`], matched to local problems by name.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeckFileProblem {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub number: Option<String>,
+    /// Easy, Medium or Hard, read as leniently as in the add form.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub difficulty: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// What importing a [`DeckFile`] changed.
+#[derive(Debug, Default)]
+pub struct DeckImport {
+    /// Whether the deck is new, rather than merged into one of the same name.
+    pub deck_created: bool,
+    /// Problems that weren't in the database yet.
+    pub added: usize,
+    /// Problems already there, which keep their progress.
+    pub existing: usize,
+    /// Entries or fields that were left out, and why.
+    pub warnings: Vec<String>,
+}
+
+/// How one problem stands to another it is linked to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RelationKind {
+    Similar,
+    FollowUp,
+    Prerequisite,
+}
+
+/// A problem linked to the one being looked at.
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub kind: RelationKind,
+    /// Whether the link was made from the other problem to this one, so a
+    /// follow-up reads as "followed up by" and a prerequisite as "builds on".
+    pub incoming: bool,
+    pub problem_id: i64,
+    pub problem_name: String,
+}
+
+/// Difficulty as labelled by the platform, not the user's own rating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// Scheduling state kept per problem so the next review date can be
+/// computed from the previous one. SM-2 uses the ease factor, FSRS the
+/// stability and difficulty, Leitner the box; all share the interval and
+/// repetition count.
+#[derive(Debug, Clone, Copy)]
+pub struct ScheduleState {
+    pub ease_factor: f64,
+    pub interval: i64,
+    pub repetitions: i64,
+    pub stability: f64,
+    pub difficulty: f64,
+    pub leitner_box: i64,
+    pub last_review: Option<DateTime<Utc>>,
+}
+
+/// A date range, inclusive on both ends, during which nothing comes due.
+#[derive(Debug, Clone, Copy)]
+pub struct Pause {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+/// A manual change to a problem's due date that is not a review, so it leaves
+/// the rating, streak and scheduling state alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DueOverride {
+    /// Pushed back by this many days.
+    Snooze(i64),
+    /// Moved to a chosen day.
+    Pin(NaiveDate),
+}
+
+/// One stored rating of a problem, used to replay or fit a scheduler.
+#[derive(Debug)]
+pub struct Review {
+    pub problem_id: i64,
+    pub rating: Rating,
+    pub reviewed_at: DateTime<Utc>,
+}
+
+/// A review that came with the code written for it.
+#[derive(Debug)]
+pub struct Attempt {
+    pub rating: Rating,
+    pub reviewed_at: DateTime<Utc>,
+    /// Time taken to solve it, if the stopwatch was used.
+    pub duration_secs: Option<i64>,
+    pub language: Option<String>,
+    pub code: String,
+}
+
+/// Times an attempt from when the problem is started until it is rated.
+/// Pausing keeps the time so far.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Stopwatch {
+    /// When the running stretch began; `None` while stopped.
+    pub started: Option<Instant>,
+    /// Time from earlier stretches, before the last pause.
+    pub banked: Duration,
+}
+
+/// What the problem list's input line is asking for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ListPrompt {
+    /// Days to snooze the selected problem by, or a date to pin it to.
+    DueDate,
+    /// A new name for the selected problem.
+    Rename,
+    /// A relation kind and the name of a problem to link the selected one to.
+    Link,
+}
+
+/// Which problems the problem list shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProblemFilter {
+    Active,
+    Mastered,
+    Suspended,
 }
 
 #[derive(Debug)]
 pub struct ViewAllProblemsScreen<'a> {
     pub db: Arc<rusqlite::Connection>,
     pub items: Vec<Row<'a>>,
+    pub problem_ids: Vec<i64>,
     pub list_state: TableState,
+    pub filter: ProblemFilter,
+    /// Deck the list is scoped to, if any.
+    pub deck: Option<Deck>,
+    /// Notes of the listed problems that have any, shown for the selected one.
+    pub notes: HashMap<i64, String>,
+    /// Timed attempts of each problem, oldest first, in seconds.
+    pub solve_times: HashMap<i64, Vec<i64>>,
+    /// Linked problems of each problem that has any.
+    pub relations: HashMap<i64, Vec<Relation>>,
+    /// The open prompt, if any, and what has been typed into it so far.
+    pub prompt: Option<(ListPrompt, String)>,
+    pub message: Option<String>,
+}
+
+/// Edits one problem's markdown notes, opened from the problem list.
+#[derive(Debug)]
+pub struct NotesScreen<'a> {
+    pub db: Arc<rusqlite::Connection>,
+    pub problem_id: i64,
+    pub problem_name: String,
+    pub editor: TextArea<'a>,
+    /// The notes as last saved, to tell whether there are unsaved edits.
+    pub saved: String,
+    /// Filter of the list the notes were opened from, restored on the way
+    /// back.
+    pub filter: ProblemFilter,
+    /// Esc was pressed with unsaved edits; a second Esc throws them away.
+    pub confirm_discard: bool,
+    pub message: Option<String>,
+}
+
+/// Pages through the code saved with a problem's attempts, to see how the
+/// solution changed between reviews.
+#[derive(Debug)]
+pub struct SolutionScreen {
+    pub problem_id: i64,
+    pub problem_name: String,
+    pub attempts: Vec<Attempt>,
+    /// Each attempt's code, highlighted once up front.
+    pub highlighted: Vec<Vec<Line<'static>>>,
+    pub current: usize,
+    /// Lines scrolled past at the top of the code.
+    pub scroll: u16,
+    /// Filter of the list the code was opened from, restored on the way back.
+    pub filter: ProblemFilter,
+}
+
+#[derive(Debug)]
+pub struct DueQueueScreen<'a> {
+    pub db: Arc<rusqlite::Connection>,
+    pub deck: Option<Deck>,
+    pub items: Vec<Row<'a>>,
+    pub list_state: TableState,
+    /// Due problems left for later days by the daily limits.
+    pub held_back: usize,
+}
+
+/// Walks through the due queue one problem at a time.
+#[derive(Debug)]
+pub struct ReviewSessionScreen {
+    pub db: Arc<rusqlite::Connection>,
+    pub deck: Option<Deck>,
+    /// Due problems and their days overdue, in queue order.
+    pub queue: Vec<(Problem, i64)>,
+    /// Reviews stored per problem when the session started, to tell problems
+    /// that were never rated.
+    pub review_counts: HashMap<i64, i64>,
+    pub current: usize,
+    pub results: Vec<SessionResult>,
+    pub skipped: usize,
+    pub held_back: usize,
+    pub failed_to_update: bool,
+    /// Times the current problem.
+    pub stopwatch: Stopwatch,
+    /// After a low rating, a linked problem worth practising next, with the
+    /// name of the one that was rated.
+    pub suggestion: Option<(String, Relation)>,
+}
+
+/// How one problem went during a review session.
+#[derive(Debug)]
+pub struct SessionResult {
+    pub problem_name: String,
+    pub rating: Rating,
+    pub duration_secs: Option<i64>,
+    pub interval: i64,
+    pub mastered: bool,
+}
+
+/// Upcoming workload: per day from today, how many reviews and new problems
+/// fall due.
+#[derive(Debug)]
+pub struct ForecastScreen {
+    pub db: Arc<rusqlite::Connection>,
+    pub deck: Option<Deck>,
+    pub days: usize,
+    pub reviews: Vec<u64>,
+    pub new_problems: Vec<u64>,
+}
+
+/// Compares scheduler configurations replayed over the review history.
+#[derive(Debug)]
+pub struct SimulatorScreen<'a> {
+    pub db: Arc<rusqlite::Connection>,
+    pub results: Vec<SimulationResult>,
+    pub items: Vec<Row<'a>>,
+    pub list_state: TableState,
+    pub days: usize,
+}
+
+/// How one scheduler configuration fared when replaying the review history.
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub name: String,
+    /// Reviews falling due on each of the simulated days, today first.
+    pub daily_reviews: Vec<u64>,
+    /// Long run reviews per day once every problem sits at its replayed
+    /// interval.
+    pub steady_per_day: f64,
+    /// Average chance of still recalling a problem when it next comes due.
+    pub expected_retention: f64,
+}
+
+/// Lists every tag for renaming and merging.
+#[derive(Debug)]
+pub struct TagScreen<'a> {
+    pub db: Arc<rusqlite::Connection>,
+    pub tags: Vec<Tag>,
+    pub items: Vec<Row<'a>>,
+    pub list_state: TableState,
+    /// New name being typed for the selected tag.
+    pub rename_input: Option<String>,
+    /// Tag picked to be merged into whichever one is selected next.
+    pub merge_from: Option<Tag>,
+    pub message: Option<String>,
+}
+
+/// What the deck screen's input line is asking for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeckPrompt {
+    /// A name for a new deck.
+    Create,
+    /// A new name for the selected deck.
+    Rename,
+    /// A description for the selected deck.
+    Describe,
+}
+
+/// Lists every deck for creating, renaming and deleting, and edits which
+/// problems belong to one.
+#[derive(Debug)]
+pub struct DeckScreen<'a> {
+    pub db: Arc<rusqlite::Connection>,
+    pub decks: Vec<Deck>,
+    pub items: Vec<Row<'a>>,
+    pub list_state: TableState,
+    /// The deck whose problems are being picked; the list then shows every
+    /// problem instead of the decks.
+    pub editing: Option<Deck>,
+    /// Every problem's id and name, and whether it is in the deck being
+    /// edited.
+    pub members: Vec<(i64, String, bool)>,
+    /// The open prompt, if any, and what has been typed into it so far.
+    pub prompt: Option<(DeckPrompt, String)>,
+    /// Delete was pressed once; pressing it again removes the deck.
+    pub confirm_delete: bool,
+    pub message: Option<String>,
 }
 
 #[derive(Debug)]
 pub struct GraphScreen {
     pub db: Arc<rusqlite::Connection>,
-    pub dates: Vec<String>,
+    pub deck: Option<Deck>,
+    /// Day of every review, for the heatmap.
+    pub dates: Vec<NaiveDate>,
+    /// Days of the current year covered by a pause, as ordinals.
+    pub paused_days: Vec<u32>,
+    /// Consecutive days with practice up to today; paused days neither count
+    /// nor break it.
+    pub streak: usize,
     pub offset: i32,
     pub current_year: usize,
 }
@@ -84,29 +502,27 @@ pub enum Action {
     NoOp,
 }
 
+#[allow(clippy::enum_variant_names)]
 pub enum ScreenAction {
     MenuNext,
     MenuPrev,
     MenuSelect,
+    /// Open the code saved with the selected problem's attempts.
+    ViewSolutions,
+}
+
+/// A spaced repetition algorithm: given a problem's state, a rating and the
+/// current time, returns the new state and when the problem is next due.
+pub trait Scheduler {
+    fn schedule(
+        &self,
+        state: &ScheduleState,
+        rating: Rating,
+        now: DateTime<Utc>,
+    ) -> (ScheduleState, DateTime<Utc>);
 }
 
 pub trait View {
-    fn handle_events(&mut self) -> io::Result<Action> {
-        let mut some_action = Action::NoOp;
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                let result = self.handle_key_event(key_event);
-                match result {
-                    Action::Quit | Action::ShouldSwitch | Action::ScreenSpecific(_) => {
-                        some_action = result;
-                    }
-                    _ => {}
-                }
-            }
-            _ => {}
-        };
-        Ok(some_action)
-    }
     fn draw(&self, frame: &mut Frame);
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Action;
 }
@@ -1,14 +1,29 @@
+#![allow(special_module_name)]
 mod app;
+mod cli;
 use std::io;
+mod db;
+mod deck_file;
 mod lib;
+mod migrations;
+mod scheduler;
 mod screens;
+mod stopwatch;
 use std::sync::Arc;
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut terminal = ratatui::init();
+    let mut conn = rusqlite::Connection::open("my_sqllite.db")?;
+    migrations::run(&mut conn)?;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        return cli::run(&conn, &args);
+    }
 
-    let conn = rusqlite::Connection::open("my_sqllite.db")?;
+    #[allow(clippy::arc_with_non_send_sync)]
     let db = Arc::new(conn);
 
+    let mut terminal = ratatui::init();
+
     let app_result = app::App::new(db).run(&mut terminal);
 
     ratatui::restore();
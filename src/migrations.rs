@@ -0,0 +1,619 @@
+use crate::db;
+use crate::lib::Rating;
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// One schema change. Each runs once, in order, inside its own transaction;
+/// `PRAGMA user_version` records how many have been applied.
+type Migration = fn(&Connection) -> rusqlite::Result<()>;
+
+/// Every migration, oldest first. Only ever append to this list: a database
+/// at version `n` has had the first `n` applied.
+const MIGRATIONS: &[Migration] = &[
+    baseline,
+    problem_ids,
+    problem_metadata,
+    tags,
+    notes,
+    solutions,
+    typed_columns,
+    decks,
+    relations,
+    added_unrated,
+];
+
+/// Columns added to `user_problems` after the original three, before schema
+/// versions were tracked.
+const BASELINE_COLUMNS: &[(&str, &str)] = &[
+    ("ease_factor", "REAL NOT NULL DEFAULT 2.5"),
+    ("interval_days", "INTEGER NOT NULL DEFAULT 0"),
+    ("repetitions", "INTEGER NOT NULL DEFAULT 0"),
+    ("due_date", "TEXT"),
+    ("stability", "REAL NOT NULL DEFAULT 0"),
+    ("difficulty", "REAL NOT NULL DEFAULT 0"),
+    ("leitner_box", "INTEGER NOT NULL DEFAULT 0"),
+    ("top_streak", "INTEGER NOT NULL DEFAULT 0"),
+    ("mastered", "INTEGER NOT NULL DEFAULT 0"),
+    ("suspended", "INTEGER NOT NULL DEFAULT 0"),
+];
+
+/// Brings the database up to the latest schema version. A failed migration
+/// is rolled back and stops the rest, leaving the database at the last
+/// version that applied cleanly.
+pub fn run(db: &mut Connection) -> rusqlite::Result<()> {
+    let version: i64 = db.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let transaction = db.transaction()?;
+        migration(&transaction)?;
+        transaction.pragma_update(None, "user_version", index as i64 + 1)?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+fn column_exists(db: &Connection, table_name: &str, column: &str) -> rusqlite::Result<bool> {
+    let query = "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?1) WHERE name = ?2)";
+    db.query_row(query, [table_name, column], |row| row.get(0))
+}
+
+/// Version 1: the schema as it stood when versioning was introduced. Older
+/// databases may be anywhere between the original single table and this,
+/// so every step checks what is already there.
+fn baseline(db: &Connection) -> rusqlite::Result<()> {
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS user_problems (
+                problem_name TEXT NOT NULL,
+                problem_rating TEXT NOT NULL,
+                entry_date TEXT NOT NULL
+            )",
+        (),
+    )?;
+
+    for (column, definition) in BASELINE_COLUMNS {
+        if !column_exists(db, "user_problems", column)? {
+            db.execute(
+                &format!("ALTER TABLE user_problems ADD COLUMN {column} {definition}"),
+                (),
+            )?;
+        }
+    }
+
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS reviews (
+                problem_id INTEGER NOT NULL,
+                rating INTEGER NOT NULL,
+                reviewed_at TEXT NOT NULL,
+                duration_secs INTEGER
+            )",
+        (),
+    )?;
+    backfill_reviews(db)?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS due_overrides (
+                problem_id INTEGER NOT NULL,
+                kind TEXT NOT NULL,
+                previous_due TEXT,
+                new_due TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            )",
+        (),
+    )?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS pauses (
+                start_date TEXT NOT NULL,
+                end_date TEXT NOT NULL
+            )",
+        (),
+    )?;
+    db.execute(
+        "CREATE TABLE IF NOT EXISTS settings (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            )",
+        (),
+    )?;
+    Ok(())
+}
+
+/// Problems entered before reviews were kept have no history at all; give
+/// each one its last known rating so it at least shows up once.
+fn backfill_reviews(db: &Connection) -> rusqlite::Result<()> {
+    db.execute(
+        "INSERT INTO reviews (problem_id, rating, reviewed_at)
+         SELECT rowid, CAST(problem_rating AS INTEGER), entry_date FROM user_problems p
+         WHERE NOT EXISTS (SELECT 1 FROM reviews r WHERE r.problem_id = p.rowid)",
+        (),
+    )?;
+    Ok(())
+}
+
+/// Version 2: an `INTEGER PRIMARY KEY` id on problems and names that are
+/// unique ignoring case. Names are normalised first; problems whose names
+/// then clash are merged into the most recently entered one, which keeps
+/// everyone's reviews and snoozes. Ids reuse the old rowids, so existing
+/// review rows keep pointing at the right problem.
+fn problem_ids(db: &Connection) -> rusqlite::Result<()> {
+    let mut statement =
+        db.prepare("SELECT rowid, problem_name FROM user_problems ORDER BY entry_date DESC")?;
+    let rows: Vec<(i64, String)> = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    let mut kept: HashMap<String, i64> = HashMap::new();
+    for (rowid, name) in rows {
+        let name = db::normalize_name(&name);
+        match kept.get(&name.to_ascii_lowercase()) {
+            Some(&keep) => {
+                for table in ["reviews", "due_overrides"] {
+                    db.execute(
+                        &format!("UPDATE {table} SET problem_id = ?1 WHERE problem_id = ?2"),
+                        (keep, rowid),
+                    )?;
+                }
+                db.execute("DELETE FROM user_problems WHERE rowid = ?1", [rowid])?;
+            }
+            None => {
+                db.execute(
+                    "UPDATE user_problems SET problem_name = ?1 WHERE rowid = ?2",
+                    (&name, rowid),
+                )?;
+                kept.insert(name.to_ascii_lowercase(), rowid);
+            }
+        }
+    }
+
+    let definitions: Vec<String> = BASELINE_COLUMNS
+        .iter()
+        .map(|(column, definition)| format!("{column} {definition}"))
+        .collect();
+    let columns: Vec<&str> = BASELINE_COLUMNS.iter().map(|(column, _)| *column).collect();
+    let columns = columns.join(", ");
+    db.execute_batch(&format!(
+        "CREATE TABLE user_problems_by_id (
+             id INTEGER PRIMARY KEY,
+             problem_name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+             problem_rating TEXT NOT NULL,
+             entry_date TEXT NOT NULL,
+             {}
+         );
+         INSERT INTO user_problems_by_id (id, problem_name, problem_rating, entry_date, {columns})
+             SELECT rowid, problem_name, problem_rating, entry_date, {columns} FROM user_problems;
+         DROP TABLE user_problems;
+         ALTER TABLE user_problems_by_id RENAME TO user_problems;",
+        definitions.join(",\n             ")
+    ))
+}
+
+/// Version 3: optional source URL, platform, number or slug, and the
+/// platform's own difficulty label.
+fn problem_metadata(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "ALTER TABLE user_problems ADD COLUMN url TEXT;
+         ALTER TABLE user_problems ADD COLUMN platform TEXT;
+         ALTER TABLE user_problems ADD COLUMN problem_number TEXT;
+         ALTER TABLE user_problems ADD COLUMN official_difficulty TEXT
+             CHECK (official_difficulty IN ('Easy', 'Medium', 'Hard'));",
+    )
+}
+
+/// Version 4: topic tags, shared between problems through a join table.
+fn tags(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "CREATE TABLE tags (
+             id INTEGER PRIMARY KEY,
+             name TEXT NOT NULL UNIQUE COLLATE NOCASE
+         );
+         CREATE TABLE problem_tags (
+             problem_id INTEGER NOT NULL,
+             tag_id INTEGER NOT NULL,
+             PRIMARY KEY (problem_id, tag_id)
+         );",
+    )
+}
+
+/// Version 5: free-form markdown notes per problem.
+fn notes(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch("ALTER TABLE user_problems ADD COLUMN notes TEXT NOT NULL DEFAULT '';")
+}
+
+/// Version 6: the code written for an attempt, and the language it is in.
+fn solutions(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "ALTER TABLE reviews ADD COLUMN language TEXT;
+         ALTER TABLE reviews ADD COLUMN solution TEXT;",
+    )
+}
+
+/// Version 7: ratings become integers checked to lie in 1-5, and every
+/// timestamp is rewritten as RFC 3339. Values that can't be read are repaired
+/// from the review history where possible, otherwise left alone, and either
+/// way listed on stderr.
+fn typed_columns(db: &Connection) -> rusqlite::Result<()> {
+    let mut report = vec![];
+    convert_reviews(db, &mut report)?;
+    convert_problems(db, &mut report)?;
+    convert_due_overrides(db, &mut report)?;
+
+    if !report.is_empty() {
+        eprintln!("Some stored values could not be read while upgrading the database:");
+        for line in &report {
+            eprintln!("  {line}");
+        }
+    }
+    Ok(())
+}
+
+/// Rebuilds `reviews` with the rating checked. Out of range ratings are
+/// clamped to 1-5; reviews whose time can't be read keep it, and stay out of
+/// the history as before.
+fn convert_reviews(db: &Connection, report: &mut Vec<String>) -> rusqlite::Result<()> {
+    let mut statement = db.prepare(
+        "SELECT rowid, problem_id, CAST(rating AS TEXT), reviewed_at,
+                duration_secs, language, solution
+         FROM reviews",
+    )?;
+    type ReviewRow = (
+        i64,
+        i64,
+        String,
+        String,
+        Option<i64>,
+        Option<String>,
+        Option<String>,
+    );
+    let rows: Vec<ReviewRow> = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+                row.get(5)?,
+                row.get(6)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    db.execute_batch(
+        "DROP TABLE reviews;
+         CREATE TABLE reviews (
+             problem_id INTEGER NOT NULL,
+             rating INTEGER NOT NULL CHECK (rating BETWEEN 1 AND 5),
+             reviewed_at TEXT NOT NULL,
+             duration_secs INTEGER,
+             language TEXT,
+             solution TEXT
+         );",
+    )?;
+    for (rowid, problem_id, rating, reviewed_at, duration_secs, language, solution) in rows {
+        let checked = Rating::parse(&rating).unwrap_or_else(|| {
+            let clamped = match rating.trim().parse::<i64>() {
+                Ok(value) if value > 5 => Rating::Perfect,
+                _ => Rating::Blackout,
+            };
+            report.push(format!(
+                "review {rowid} of problem {problem_id}: rating {rating:?} stored as {clamped}"
+            ));
+            clamped
+        });
+        let reviewed_at = match db::parse_timestamp(&reviewed_at) {
+            Some(parsed) => db::timestamp(&parsed),
+            None => {
+                report.push(format!(
+                    "review {rowid} of problem {problem_id}: unreadable time {reviewed_at:?} left as is"
+                ));
+                reviewed_at
+            }
+        };
+        db.execute(
+            "INSERT INTO reviews
+                 (rowid, problem_id, rating, reviewed_at, duration_secs, language, solution)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            (
+                rowid,
+                problem_id,
+                checked,
+                reviewed_at,
+                duration_secs,
+                language,
+                solution,
+            ),
+        )?;
+    }
+    Ok(())
+}
+
+/// Swaps `problem_rating` for a checked integer column and rewrites entry and
+/// due dates. An unreadable rating or entry date falls back to the latest
+/// review, or a 1 and the time of the upgrade without one; an unreadable due
+/// date is cleared, which makes the problem due.
+fn convert_problems(db: &Connection, report: &mut Vec<String>) -> rusqlite::Result<()> {
+    let mut statement = db.prepare(
+        "SELECT id, problem_name, CAST(problem_rating AS TEXT), entry_date, due_date
+         FROM user_problems",
+    )?;
+    type ProblemRow = (i64, String, String, String, Option<String>);
+    let rows: Vec<ProblemRow> = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+    let latest_reviews = latest_reviews(db)?;
+
+    db.execute_batch(
+        "ALTER TABLE user_problems ADD COLUMN rating INTEGER NOT NULL DEFAULT 1
+             CHECK (rating BETWEEN 1 AND 5);",
+    )?;
+    let now = chrono::Utc::now();
+    for (id, name, rating, entry_date, due_date) in rows {
+        let latest = latest_reviews.get(&id);
+        let rating = match Rating::parse(&rating) {
+            Some(parsed) => parsed,
+            None => {
+                let fallback = latest.map_or(Rating::Blackout, |(rating, _)| *rating);
+                report.push(format!("{name}: rating {rating:?} stored as {fallback}"));
+                fallback
+            }
+        };
+        let entry_date = match db::parse_timestamp(&entry_date) {
+            Some(parsed) => parsed,
+            None => {
+                let fallback = latest.map_or(now, |(_, reviewed_at)| *reviewed_at);
+                report.push(format!(
+                    "{name}: entry date {entry_date:?} stored as {}",
+                    db::timestamp(&fallback)
+                ));
+                fallback
+            }
+        };
+        let due_date = due_date.and_then(|due_date| match db::parse_timestamp(&due_date) {
+            Some(parsed) => Some(parsed),
+            None => {
+                report.push(format!(
+                    "{name}: due date {due_date:?} cleared, so it is due now"
+                ));
+                None
+            }
+        });
+        db.execute(
+            "UPDATE user_problems SET rating = ?1, entry_date = ?2, due_date = ?3 WHERE id = ?4",
+            (
+                rating,
+                db::timestamp(&entry_date),
+                due_date.as_ref().map(db::timestamp),
+                id,
+            ),
+        )?;
+    }
+    db.execute_batch(
+        "ALTER TABLE user_problems DROP COLUMN problem_rating;
+         ALTER TABLE user_problems RENAME COLUMN rating TO problem_rating;",
+    )
+}
+
+/// Each problem's most recent readable review.
+fn latest_reviews(db: &Connection) -> rusqlite::Result<HashMap<i64, (Rating, DateTime<Utc>)>> {
+    let mut latest = HashMap::new();
+    for review in db::load_reviews(db)? {
+        latest.insert(review.problem_id, (review.rating, review.reviewed_at));
+    }
+    Ok(latest)
+}
+
+/// Rewrites the times logged with snoozes and pins. The log isn't read back,
+/// so unreadable ones are only reported.
+fn convert_due_overrides(db: &Connection, report: &mut Vec<String>) -> rusqlite::Result<()> {
+    let mut statement = db.prepare(
+        "SELECT rowid, problem_id, previous_due, new_due, created_at FROM due_overrides",
+    )?;
+    type OverrideRow = (i64, i64, Option<String>, String, String);
+    let rows: Vec<OverrideRow> = statement
+        .query_map([], |row| {
+            Ok((
+                row.get(0)?,
+                row.get(1)?,
+                row.get(2)?,
+                row.get(3)?,
+                row.get(4)?,
+            ))
+        })?
+        .collect::<rusqlite::Result<_>>()?;
+
+    for (rowid, problem_id, previous_due, new_due, created_at) in rows {
+        let mut convert = |text: String| match db::parse_timestamp(&text) {
+            Some(parsed) => db::timestamp(&parsed),
+            None => {
+                report.push(format!(
+                    "snooze {rowid} of problem {problem_id}: unreadable time {text:?} left as is"
+                ));
+                text
+            }
+        };
+        let previous_due = previous_due.map(&mut convert);
+        let new_due = convert(new_due);
+        let created_at = convert(created_at);
+        db.execute(
+            "UPDATE due_overrides SET previous_due = ?1, new_due = ?2, created_at = ?3
+             WHERE rowid = ?4",
+            (previous_due, new_due, created_at, rowid),
+        )?;
+    }
+    Ok(())
+}
+
+/// Version 8: named collections of problems, such as a study list, with
+/// their members in a join table.
+fn decks(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "CREATE TABLE decks (
+             id INTEGER PRIMARY KEY,
+             name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+             description TEXT NOT NULL DEFAULT ''
+         );
+         CREATE TABLE deck_problems (
+             deck_id INTEGER NOT NULL,
+             problem_id INTEGER NOT NULL,
+             PRIMARY KEY (deck_id, problem_id)
+         );",
+    )
+}
+
+/// Version 9: typed links between problems. A row reads "problem is
+/// similar to / a follow-up of / a prerequisite of related". Deleting a
+/// problem removes its links from both ends.
+fn relations(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "CREATE TABLE problem_relations (
+             problem_id INTEGER NOT NULL,
+             related_id INTEGER NOT NULL,
+             kind TEXT NOT NULL CHECK (kind IN ('similar', 'follow-up', 'prerequisite')),
+             PRIMARY KEY (problem_id, related_id, kind),
+             CHECK (problem_id <> related_id)
+         );
+         CREATE TRIGGER unlink_deleted_problems AFTER DELETE ON user_problems
+         BEGIN
+             DELETE FROM problem_relations
+             WHERE problem_id = OLD.id OR related_id = OLD.id;
+         END;",
+    )
+}
+
+/// Version 10: marks problems added without a rating, such as deck imports,
+/// whose first rating is what introduces them. Ones never rated yet are
+/// marked; any imported problem already rated can't be told apart and counts
+/// as added rated.
+fn added_unrated(db: &Connection) -> rusqlite::Result<()> {
+    db.execute_batch(
+        "ALTER TABLE user_problems ADD COLUMN added_unrated INTEGER NOT NULL DEFAULT 0;
+         UPDATE user_problems SET added_unrated = 1
+         WHERE id NOT IN (SELECT problem_id FROM reviews);",
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A database as left by versions before schema tracking: text ratings,
+    /// `Display`-formatted times, names differing only in case and spacing,
+    /// and values that can't be read.
+    fn unversioned() -> Connection {
+        let db = Connection::open_in_memory().unwrap();
+        db.execute_batch(
+            "CREATE TABLE user_problems (
+                 problem_name TEXT NOT NULL,
+                 problem_rating TEXT NOT NULL,
+                 entry_date TEXT NOT NULL,
+                 due_date TEXT
+             );
+             CREATE TABLE reviews (
+                 problem_id INTEGER NOT NULL,
+                 rating INTEGER NOT NULL,
+                 reviewed_at TEXT NOT NULL,
+                 duration_secs INTEGER
+             );
+             INSERT INTO user_problems VALUES
+                 ('Two Sum', '3', '2024-05-01 18:30:00.123 UTC', '2024-05-04 18:30:00.123 UTC'),
+                 ('two  sum ', '5', '2024-05-02 09:00:00 UTC', NULL),
+                 ('Valid Parentheses', 'x', 'yesterday', 'someday');
+             INSERT INTO reviews VALUES
+                 (1, 3, '2024-05-01 18:30:00.123 UTC', 600),
+                 (2, 5, '2024-05-02 09:00:00 UTC', NULL),
+                 (3, 9, '2024-04-30 08:00:00 UTC', NULL);",
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn upgrades_an_unversioned_database() {
+        let mut db = unversioned();
+        run(&mut db).unwrap();
+
+        let version: i64 = db
+            .pragma_query_value(None, "user_version", |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, MIGRATIONS.len() as i64);
+
+        let mut statement = db
+            .prepare(
+                "SELECT id, problem_name, problem_rating, entry_date, due_date
+                 FROM user_problems ORDER BY id",
+            )
+            .unwrap();
+        let problems: Vec<(i64, String, i64, String, Option<String>)> = statement
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    row.get(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    row.get(4)?,
+                ))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        // The two spellings of Two Sum merge into the later one; Valid
+        // Parentheses takes its rating and entry date from its review.
+        assert_eq!(
+            problems,
+            [
+                (
+                    2,
+                    "two sum".to_string(),
+                    5,
+                    "2024-05-02T09:00:00.000Z".to_string(),
+                    None
+                ),
+                (
+                    3,
+                    "Valid Parentheses".to_string(),
+                    5,
+                    "2024-04-30T08:00:00.000Z".to_string(),
+                    None
+                ),
+            ]
+        );
+
+        let mut statement = db
+            .prepare(
+                "SELECT problem_id, rating, reviewed_at, duration_secs FROM reviews ORDER BY rowid",
+            )
+            .unwrap();
+        let reviews: Vec<(i64, i64, String, Option<i64>)> = statement
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            reviews,
+            [
+                (2, 3, "2024-05-01T18:30:00.123Z".to_string(), Some(600)),
+                (2, 5, "2024-05-02T09:00:00.000Z".to_string(), None),
+                (3, 5, "2024-04-30T08:00:00.000Z".to_string(), None),
+            ]
+        );
+        assert_eq!(db::load_reviews(&db).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn running_again_changes_nothing() {
+        let mut db = unversioned();
+        run(&mut db).unwrap();
+        run(&mut db).unwrap();
+        let problems: i64 = db
+            .query_row("SELECT COUNT(*) FROM user_problems", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(problems, 2);
+    }
+}
@@ -0,0 +1,393 @@
+use crate::db;
+use crate::lib::{Rating, Review, ScheduleState, Scheduler};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+
+const DECAY: f64 = -0.5;
+/// Chosen so that retrievability is exactly 90% after `stability` days.
+const FACTOR: f64 = 19.0 / 81.0;
+const MAX_INTERVAL: f64 = 36500.0;
+const MIN_STABILITY: f64 = 0.1;
+
+/// Published FSRS-4.5 defaults, used until weights are fitted.
+pub const DEFAULT_WEIGHTS: [f64; 17] = [
+    0.4872, 1.4003, 3.7145, 13.8206, 5.1618, 1.2298, 0.8975, 0.031, 1.6474, 0.1367, 1.0461, 2.1072,
+    0.0793, 0.3246, 1.587, 0.2272, 2.8755,
+];
+pub const DEFAULT_RETENTION: f64 = 0.9;
+
+/// Lower and upper bound for every weight while fitting.
+const WEIGHT_BOUNDS: [(f64, f64); 17] = [
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (0.1, 100.0),
+    (1.0, 10.0),
+    (0.1, 5.0),
+    (0.1, 5.0),
+    (0.0, 0.75),
+    (0.0, 4.0),
+    (0.0, 0.8),
+    (0.01, 3.0),
+    (0.1, 5.0),
+    (0.01, 0.2),
+    (0.01, 0.9),
+    (0.01, 2.0),
+    (0.0, 1.0),
+    (1.0, 6.0),
+];
+
+/// Fewer reviews than this (not counting each problem's first one) is not
+/// enough signal to move the weights away from the defaults.
+pub const MIN_FIT_REVIEWS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct FsrsParams {
+    pub weights: [f64; 17],
+    pub retention: f64,
+}
+
+impl Default for FsrsParams {
+    fn default() -> Self {
+        Self {
+            weights: DEFAULT_WEIGHTS,
+            retention: DEFAULT_RETENTION,
+        }
+    }
+}
+
+impl FsrsParams {
+    /// Reads `fsrs_weights` and `fsrs_retention`, falling back to the defaults
+    /// for anything missing or malformed.
+    pub fn load(db: &Connection) -> rusqlite::Result<Self> {
+        let mut params = Self::default();
+        if let Some(weights) = db::get_setting(db, "fsrs_weights")?
+            && let Some(weights) = parse_weights(&weights)
+        {
+            params.weights = weights;
+        }
+        if let Some(retention) = db::get_setting(db, "fsrs_retention")?
+            && let Some(retention) = parse_retention(&retention)
+        {
+            params.retention = retention;
+        }
+        Ok(params)
+    }
+
+    pub fn save(&self, db: &Connection) -> rusqlite::Result<()> {
+        let weights: Vec<String> = self.weights.iter().map(|w| format!("{w:.4}")).collect();
+        db::set_setting(db, "fsrs_weights", &weights.join(","))?;
+        db::set_setting(db, "fsrs_retention", &self.retention.to_string())
+    }
+}
+
+/// Reads a comma separated list of the 17 weights, each finite and within
+/// the bounds fitting keeps it to.
+pub fn parse_weights(value: &str) -> Option<[f64; 17]> {
+    let parsed: Vec<f64> = value
+        .split(',')
+        .map(|w| w.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let weights: [f64; 17] = parsed.try_into().ok()?;
+    weights
+        .iter()
+        .zip(WEIGHT_BOUNDS)
+        .all(|(w, (low, high))| w.is_finite() && (low..=high).contains(w))
+        .then_some(weights)
+}
+
+/// Accepts retention targets FSRS can sensibly schedule for.
+pub fn parse_retention(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|r| (0.7..=0.97).contains(r))
+}
+
+/// Maps the app's 1-5 rating onto FSRS grades (1 Again, 2 Hard, 3 Good, 4 Easy),
+/// keeping SM-2's rule that anything below 3 is a lapse.
+fn grade(rating: Rating) -> usize {
+    match rating {
+        Rating::Blackout | Rating::Incorrect => 1,
+        Rating::Difficult => 2,
+        Rating::Hesitant => 3,
+        Rating::Perfect => 4,
+    }
+}
+
+pub fn retrievability(elapsed_days: f64, stability: f64) -> f64 {
+    (1.0 + FACTOR * elapsed_days / stability).powf(DECAY)
+}
+
+/// Days until retrievability drops to `retention`.
+pub fn next_interval(stability: f64, retention: f64) -> i64 {
+    let days = stability / FACTOR * (retention.powf(1.0 / DECAY) - 1.0);
+    days.round().clamp(1.0, MAX_INTERVAL) as i64
+}
+
+fn initial_difficulty(w: &[f64; 17], grade: usize) -> f64 {
+    (w[4] - (grade as f64 - 3.0) * w[5]).clamp(1.0, 10.0)
+}
+
+fn next_difficulty(w: &[f64; 17], difficulty: f64, grade: usize) -> f64 {
+    let next = difficulty - w[6] * (grade as f64 - 3.0);
+    (w[7] * initial_difficulty(w, 3) + (1.0 - w[7]) * next).clamp(1.0, 10.0)
+}
+
+fn recall_stability(w: &[f64; 17], difficulty: f64, stability: f64, r: f64, grade: usize) -> f64 {
+    let hard_penalty = if grade == 2 { w[15] } else { 1.0 };
+    let easy_bonus = if grade == 4 { w[16] } else { 1.0 };
+    stability
+        * (1.0
+            + w[8].exp()
+                * (11.0 - difficulty)
+                * stability.powf(-w[9])
+                * ((w[10] * (1.0 - r)).exp() - 1.0)
+                * hard_penalty
+                * easy_bonus)
+}
+
+fn forget_stability(w: &[f64; 17], difficulty: f64, stability: f64, r: f64) -> f64 {
+    let next = w[11]
+        * difficulty.powf(-w[12])
+        * ((stability + 1.0).powf(w[13]) - 1.0)
+        * (w[14] * (1.0 - r)).exp();
+    next.min(stability)
+}
+
+/// Memory state `(stability, difficulty)` after one review. `memory` is the
+/// state before the review and how many days have passed since the last one.
+fn step(w: &[f64; 17], memory: Option<(f64, f64, f64)>, grade: usize) -> (f64, f64) {
+    match memory {
+        None => (
+            w[grade - 1].max(MIN_STABILITY),
+            initial_difficulty(w, grade),
+        ),
+        Some((stability, difficulty, elapsed_days)) => {
+            let r = retrievability(elapsed_days, stability);
+            let next_stability = if grade == 1 {
+                forget_stability(w, difficulty, stability, r)
+            } else {
+                recall_stability(w, difficulty, stability, r, grade)
+            };
+            (
+                next_stability.max(MIN_STABILITY),
+                next_difficulty(w, difficulty, grade),
+            )
+        }
+    }
+}
+
+fn elapsed_days(from: DateTime<Utc>, to: DateTime<Utc>) -> f64 {
+    ((to - from).num_seconds() as f64 / 86_400.0).max(0.0)
+}
+
+impl Scheduler for FsrsParams {
+    fn schedule(
+        &self,
+        state: &ScheduleState,
+        rating: Rating,
+        now: DateTime<Utc>,
+    ) -> (ScheduleState, DateTime<Utc>) {
+        let grade = grade(rating);
+        // A problem last scheduled by another algorithm has no stability yet, so
+        // it starts over as if this were its first review.
+        let memory = match state.last_review {
+            Some(last) if state.stability > 0.0 => {
+                Some((state.stability, state.difficulty, elapsed_days(last, now)))
+            }
+            _ => None,
+        };
+        let (stability, difficulty) = step(&self.weights, memory, grade);
+        let interval = next_interval(stability, self.retention);
+
+        let next = ScheduleState {
+            interval,
+            repetitions: if grade == 1 { 0 } else { state.repetitions + 1 },
+            stability,
+            difficulty,
+            last_review: Some(now),
+            ..*state
+        };
+        (next, now + Duration::days(interval))
+    }
+}
+
+#[derive(Debug)]
+pub struct FitReport {
+    pub params: FsrsParams,
+    pub reviews_used: usize,
+    pub loss_before: f64,
+    pub loss_after: f64,
+}
+
+/// Per problem, the grade of every review and the days since the one before.
+fn review_sequences(reviews: &[Review]) -> Vec<Vec<(f64, usize)>> {
+    let mut sequences: Vec<Vec<(f64, usize)>> = vec![];
+    let mut previous: Option<&Review> = None;
+    for review in reviews {
+        match previous {
+            Some(last) if last.problem_id == review.problem_id => {
+                let elapsed = elapsed_days(last.reviewed_at, review.reviewed_at);
+                if let Some(sequence) = sequences.last_mut() {
+                    sequence.push((elapsed, grade(review.rating)));
+                }
+            }
+            _ => sequences.push(vec![(0.0, grade(review.rating))]),
+        }
+        previous = Some(review);
+    }
+    sequences
+}
+
+/// Mean log loss of the predicted recall probability against whether each
+/// review was actually recalled.
+fn log_loss(w: &[f64; 17], sequences: &[Vec<(f64, usize)>]) -> f64 {
+    let mut total = 0.0;
+    let mut count = 0;
+    for sequence in sequences {
+        let mut memory: Option<(f64, f64)> = None;
+        for &(elapsed, grade) in sequence {
+            if let Some((stability, _)) = memory {
+                let r = retrievability(elapsed, stability).clamp(1e-4, 1.0 - 1e-4);
+                let recalled = if grade > 1 { 1.0 } else { 0.0 };
+                total -= recalled * r.ln() + (1.0 - recalled) * (1.0 - r).ln();
+                count += 1;
+            }
+            memory = Some(step(w, memory.map(|(s, d)| (s, d, elapsed)), grade));
+        }
+    }
+    if count == 0 {
+        0.0
+    } else {
+        total / count as f64
+    }
+}
+
+/// Log loss plus a small pull towards the defaults so a short history cannot
+/// drag the weights somewhere absurd.
+fn objective(w: &[f64; 17], sequences: &[Vec<(f64, usize)>]) -> f64 {
+    let penalty: f64 = w
+        .iter()
+        .zip(DEFAULT_WEIGHTS.iter())
+        .map(|(w, d)| ((w - d) / d.max(0.1)).powi(2))
+        .sum();
+    log_loss(w, sequences) + 0.01 * penalty
+}
+
+/// Fits FSRS weights to the stored review history with Adam over numerical
+/// gradients. Returns `None` when there is not enough history to fit.
+pub fn fit(reviews: &[Review], retention: f64) -> Option<FitReport> {
+    const ITERATIONS: usize = 250;
+    const LEARNING_RATE: f64 = 0.01;
+    const EPSILON: f64 = 1e-4;
+
+    let sequences = review_sequences(reviews);
+    let reviews_used: usize = sequences.iter().map(|s| s.len().saturating_sub(1)).sum();
+    if reviews_used < MIN_FIT_REVIEWS {
+        return None;
+    }
+
+    let mut w = DEFAULT_WEIGHTS;
+    let mut m = [0.0; 17];
+    let mut v = [0.0; 17];
+    for iteration in 1..=ITERATIONS {
+        for i in 0..w.len() {
+            // Step relative to the weight's range so large and small weights
+            // move at a comparable pace.
+            let (low, high) = WEIGHT_BOUNDS[i];
+            let scale = high - low;
+
+            let mut up = w;
+            up[i] = (w[i] + EPSILON * scale).min(high);
+            let mut down = w;
+            down[i] = (w[i] - EPSILON * scale).max(low);
+            let gradient = (objective(&up, &sequences) - objective(&down, &sequences))
+                / ((up[i] - down[i]) / scale).max(f64::EPSILON);
+
+            m[i] = 0.9 * m[i] + 0.1 * gradient;
+            v[i] = 0.999 * v[i] + 0.001 * gradient * gradient;
+            let m_hat = m[i] / (1.0 - 0.9f64.powi(iteration as i32));
+            let v_hat = v[i] / (1.0 - 0.999f64.powi(iteration as i32));
+            w[i] = (w[i] - LEARNING_RATE * scale * m_hat / (v_hat.sqrt() + 1e-8)).clamp(low, high);
+        }
+    }
+
+    Some(FitReport {
+        params: FsrsParams {
+            weights: w,
+            retention,
+        },
+        reviews_used,
+        loss_before: log_loss(&DEFAULT_WEIGHTS, &sequences),
+        loss_after: log_loss(&w, &sequences),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn weights_must_be_seventeen_numbers() {
+        let defaults: Vec<String> = DEFAULT_WEIGHTS.iter().map(f64::to_string).collect();
+        assert_eq!(parse_weights(&defaults.join(",")), Some(DEFAULT_WEIGHTS));
+        assert_eq!(parse_weights(&defaults[..16].join(",")), None);
+        assert_eq!(parse_weights(&["a"; 17].join(",")), None);
+    }
+
+    #[test]
+    fn weights_must_be_finite_and_in_bounds() {
+        let with = |index: usize, value: &str| {
+            let mut weights: Vec<String> = DEFAULT_WEIGHTS.iter().map(f64::to_string).collect();
+            weights[index] = value.to_string();
+            parse_weights(&weights.join(","))
+        };
+        assert_eq!(with(3, "NaN"), None);
+        assert_eq!(with(3, "inf"), None);
+        assert_eq!(with(0, "-1.5"), None);
+        assert_eq!(with(16, "6.5"), None);
+        assert!(with(16, "6").is_some());
+    }
+
+    #[test]
+    fn fitting_does_not_make_the_history_less_likely() {
+        // Someone who forgets much faster than the defaults assume: every
+        // review after the first couple of days is a lapse.
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 0, 0).unwrap();
+        let mut reviews = vec![];
+        for problem_id in 1..=20 {
+            for (days, rating) in [
+                (0, Rating::Hesitant),
+                (1, Rating::Hesitant),
+                (4, Rating::Incorrect),
+                (5, Rating::Hesitant),
+                (15, Rating::Blackout),
+            ] {
+                reviews.push(Review {
+                    problem_id,
+                    rating,
+                    reviewed_at: start + Duration::days(days + problem_id),
+                });
+            }
+        }
+
+        let report = fit(&reviews, 0.9).unwrap();
+        assert_eq!(report.reviews_used, 80);
+        assert!(report.loss_after <= report.loss_before);
+        assert_eq!(report.params.retention, 0.9);
+    }
+
+    #[test]
+    fn too_little_history_is_not_fitted() {
+        let reviews = [Review {
+            problem_id: 1,
+            rating: Rating::Perfect,
+            reviewed_at: Utc::now(),
+        }];
+        assert!(fit(&reviews, 0.9).is_none());
+    }
+}
@@ -0,0 +1,143 @@
+use crate::db;
+use crate::lib::Rating;
+use rusqlite::Connection;
+
+pub const DEFAULT_STREAK: i64 = 2;
+pub const DEFAULT_MIN_RATING: Rating = Rating::Perfect;
+
+/// Retires a problem as mastered after `streak` consecutive ratings of at
+/// least `min_rating`, like upstream srl's two 5s in a row.
+#[derive(Debug, Clone, Copy)]
+pub struct GraduationRule {
+    pub streak: i64,
+    pub min_rating: Rating,
+}
+
+impl Default for GraduationRule {
+    fn default() -> Self {
+        Self {
+            streak: DEFAULT_STREAK,
+            min_rating: DEFAULT_MIN_RATING,
+        }
+    }
+}
+
+pub fn parse_streak(value: &str) -> Option<i64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|streak| *streak >= 1)
+}
+
+pub fn parse_min_rating(value: &str) -> Option<Rating> {
+    Rating::parse(value)
+}
+
+impl GraduationRule {
+    /// Reads `mastery_streak` and `mastery_rating`, defaulting to two 5s.
+    pub fn load(db: &Connection) -> rusqlite::Result<Self> {
+        let mut rule = Self::default();
+        if let Some(value) = db::get_setting(db, "mastery_streak")?
+            && let Some(streak) = parse_streak(&value)
+        {
+            rule.streak = streak;
+        }
+        if let Some(value) = db::get_setting(db, "mastery_rating")?
+            && let Some(min_rating) = parse_min_rating(&value)
+        {
+            rule.min_rating = min_rating;
+        }
+        Ok(rule)
+    }
+
+    /// The new top-rating streak after `rating`, and whether it graduates the
+    /// problem.
+    pub fn apply(&self, previous_streak: i64, rating: Rating) -> (i64, bool) {
+        let streak = if rating >= self.min_rating {
+            previous_streak + 1
+        } else {
+            0
+        };
+        (streak, streak >= self.streak)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Applies each rating in turn from no streak, returning the streak and
+    /// whether the problem is mastered after every one.
+    fn run(rule: GraduationRule, ratings: &[Rating]) -> Vec<(i64, bool)> {
+        let mut streak = 0;
+        ratings
+            .iter()
+            .map(|&rating| {
+                let (next, mastered) = rule.apply(streak, rating);
+                streak = next;
+                (streak, mastered)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_streak_of_top_ratings_masters_the_problem() {
+        assert_eq!(
+            run(GraduationRule::default(), &[Rating::Perfect; 2]),
+            [(1, false), (2, true)]
+        );
+        let rule = GraduationRule {
+            streak: 3,
+            ..GraduationRule::default()
+        };
+        assert_eq!(
+            run(rule, &[Rating::Perfect; 3]),
+            [(1, false), (2, false), (3, true)]
+        );
+    }
+
+    #[test]
+    fn a_lower_rating_resets_the_streak() {
+        assert_eq!(
+            run(
+                GraduationRule::default(),
+                &[
+                    Rating::Perfect,
+                    Rating::Hesitant,
+                    Rating::Perfect,
+                    Rating::Perfect
+                ]
+            ),
+            [(1, false), (0, false), (1, false), (2, true)]
+        );
+    }
+
+    #[test]
+    fn the_minimum_rating_counts_toward_the_streak() {
+        let rule = GraduationRule {
+            min_rating: Rating::Hesitant,
+            ..GraduationRule::default()
+        };
+        assert_eq!(
+            run(rule, &[Rating::Hesitant, Rating::Perfect]),
+            [(1, false), (2, true)]
+        );
+        assert_eq!(
+            run(
+                rule,
+                &[Rating::Hesitant, Rating::Difficult, Rating::Hesitant]
+            ),
+            [(1, false), (0, false), (1, false)]
+        );
+    }
+
+    #[test]
+    fn parses_the_streak_and_rating() {
+        assert_eq!(parse_streak(" 3 "), Some(3));
+        assert_eq!(parse_streak("0"), None);
+        assert_eq!(parse_streak("two"), None);
+        assert_eq!(parse_min_rating("4"), Some(Rating::Hesitant));
+        assert_eq!(parse_min_rating("6"), None);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::db;
+use crate::lib::{Rating, ScheduleState, Scheduler};
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::Connection;
+
+pub const DEFAULT_BOX_COUNT: usize = 5;
+const MAX_BOX_COUNT: usize = 20;
+
+/// Leitner boxes: a passing rating (3 or more) moves the problem up one box, a
+/// lapse sends it back to the first. Each box has a fixed review interval.
+#[derive(Debug, Clone)]
+pub struct Leitner {
+    /// Interval in days for each box, first box first.
+    pub intervals: Vec<i64>,
+}
+
+impl Default for Leitner {
+    fn default() -> Self {
+        Self {
+            intervals: doubling_intervals(DEFAULT_BOX_COUNT),
+        }
+    }
+}
+
+/// 1, 2, 4, ... days, one entry per box.
+fn doubling_intervals(box_count: usize) -> Vec<i64> {
+    (0..box_count).map(|i| 1 << i.min(16)).collect()
+}
+
+/// Parses a comma-separated list of positive day counts, one per box.
+pub fn parse_intervals(value: &str) -> Option<Vec<i64>> {
+    let intervals: Vec<i64> = value
+        .split(',')
+        .map(|days| days.trim().parse::<i64>())
+        .collect::<Result<_, _>>()
+        .ok()?;
+    if !(1..=MAX_BOX_COUNT).contains(&intervals.len()) || intervals.iter().any(|&days| days < 1) {
+        return None;
+    }
+    Some(intervals)
+}
+
+pub fn parse_box_count(value: &str) -> Option<usize> {
+    value
+        .trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|count| (1..=MAX_BOX_COUNT).contains(count))
+}
+
+impl Leitner {
+    /// Reads `leitner_intervals` and `leitner_boxes`. The box count trims the
+    /// intervals or extends them by doubling the last one.
+    pub fn load(db: &Connection) -> rusqlite::Result<Self> {
+        let mut intervals = match db::get_setting(db, "leitner_intervals")? {
+            Some(value) => {
+                parse_intervals(&value).unwrap_or_else(|| doubling_intervals(DEFAULT_BOX_COUNT))
+            }
+            None => doubling_intervals(DEFAULT_BOX_COUNT),
+        };
+        if let Some(value) = db::get_setting(db, "leitner_boxes")?
+            && let Some(box_count) = parse_box_count(&value)
+        {
+            while intervals.len() < box_count {
+                let last = intervals.last().copied().unwrap_or(1);
+                intervals.push(last * 2);
+            }
+            intervals.truncate(box_count);
+        }
+        Ok(Self { intervals })
+    }
+}
+
+impl Scheduler for Leitner {
+    fn schedule(
+        &self,
+        state: &ScheduleState,
+        rating: Rating,
+        now: DateTime<Utc>,
+    ) -> (ScheduleState, DateTime<Utc>) {
+        let box_count = self.intervals.len().max(1) as i64;
+        // Boxes are numbered from 1; 0 means the problem has never been boxed.
+        let (leitner_box, repetitions) = if rating < Rating::Difficult {
+            (1, 0)
+        } else if state.leitner_box == 0 {
+            (1, state.repetitions + 1)
+        } else {
+            (
+                (state.leitner_box + 1).min(box_count),
+                state.repetitions + 1,
+            )
+        };
+        let interval = self
+            .intervals
+            .get((leitner_box - 1) as usize)
+            .copied()
+            .unwrap_or(1);
+
+        let next = ScheduleState {
+            interval,
+            repetitions,
+            leitner_box,
+            last_review: Some(now),
+            ..*state
+        };
+        (next, now + Duration::days(interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 18, 30, 0).unwrap()
+    }
+
+    fn leitner() -> Leitner {
+        Leitner {
+            intervals: vec![1, 3, 7],
+        }
+    }
+
+    /// Rates a fresh problem with each rating in turn, returning the box and
+    /// interval after every one.
+    fn run(ratings: &[Rating]) -> Vec<(i64, i64)> {
+        let mut state = ScheduleState::default();
+        ratings
+            .iter()
+            .map(|&rating| {
+                let (next, due) = leitner().schedule(&state, rating, now());
+                assert_eq!(due, now() + Duration::days(next.interval));
+                state = next;
+                (state.leitner_box, state.interval)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn passing_moves_up_a_box() {
+        assert_eq!(
+            run(&[Rating::Difficult, Rating::Hesitant, Rating::Perfect]),
+            [(1, 1), (2, 3), (3, 7)]
+        );
+    }
+
+    #[test]
+    fn a_lapse_goes_back_to_the_first_box() {
+        for lapse in [Rating::Blackout, Rating::Incorrect] {
+            assert_eq!(
+                run(&[Rating::Perfect, Rating::Perfect, lapse, Rating::Perfect]),
+                [(1, 1), (2, 3), (1, 1), (2, 3)]
+            );
+        }
+    }
+
+    #[test]
+    fn the_last_box_is_a_ceiling() {
+        assert_eq!(
+            run(&[Rating::Perfect; 5]),
+            [(1, 1), (2, 3), (3, 7), (3, 7), (3, 7)]
+        );
+    }
+
+    #[test]
+    fn parses_intervals_and_box_counts() {
+        assert_eq!(parse_intervals(" 1, 3,7 "), Some(vec![1, 3, 7]));
+        assert_eq!(parse_box_count(" 20 "), Some(20));
+        let twenty = vec!["1"; 20].join(",");
+        assert_eq!(parse_intervals(&twenty).map(|days| days.len()), Some(20));
+    }
+
+    #[test]
+    fn rejects_bad_intervals_and_box_counts() {
+        for value in ["", ",", "1,,3", "1,0,3", "1,-2", "1,two"] {
+            assert_eq!(parse_intervals(value), None, "{value:?}");
+        }
+        assert_eq!(parse_intervals(&vec!["1"; 21].join(",")), None);
+        for value in ["", "0", "21", "-1", "five"] {
+            assert_eq!(parse_box_count(value), None, "{value:?}");
+        }
+    }
+}
@@ -0,0 +1,270 @@
+use crate::db;
+use crate::lib::{Problem, Review};
+use chrono::NaiveDate;
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+
+/// Caps on how much is shown per day. A problem is new until it has been rated
+/// once since being added, not counting a rating given when adding it;
+/// everything else is a review. `None` means no cap.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DailyLimits {
+    pub max_new: Option<usize>,
+    pub max_reviews: Option<usize>,
+}
+
+/// Today's queue after the daily limits, and how many due problems were held
+/// back for the following days.
+#[derive(Debug, Default)]
+pub struct DailyQueue {
+    pub problems: Vec<(Problem, i64)>,
+    pub held_back: usize,
+}
+
+/// Accepts a non-negative cap, where 0 turns the cap off.
+pub fn parse_limit(value: &str) -> Option<usize> {
+    value.trim().parse::<usize>().ok()
+}
+
+fn load_limit(db: &Connection, key: &str) -> rusqlite::Result<Option<usize>> {
+    Ok(db::get_setting(db, key)?
+        .as_deref()
+        .and_then(parse_limit)
+        .filter(|limit| *limit > 0))
+}
+
+/// Which of a problem's ratings, counting from 0, introduces it: the second
+/// for a problem rated when it was added, and the first for one added
+/// unrated.
+fn introducing_rating(added_unrated: &HashSet<i64>, problem_id: i64) -> i64 {
+    if added_unrated.contains(&problem_id) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Whether a problem has yet to be introduced, given how many ratings each
+/// problem has and which were added unrated.
+pub fn is_new(
+    review_counts: &HashMap<i64, i64>,
+    added_unrated: &HashSet<i64>,
+    problem_id: i64,
+) -> bool {
+    review_counts.get(&problem_id).copied().unwrap_or(0)
+        <= introducing_rating(added_unrated, problem_id)
+}
+
+impl DailyLimits {
+    /// Reads `max_new_per_day` and `max_reviews_per_day`.
+    pub fn load(db: &Connection) -> rusqlite::Result<Self> {
+        Ok(Self {
+            max_new: load_limit(db, "max_new_per_day")?,
+            max_reviews: load_limit(db, "max_reviews_per_day")?,
+        })
+    }
+
+    /// Caps a per-day forecast starting today, carrying whatever does not fit
+    /// on one day over to the next. Today only has room for what is left after
+    /// the `(new, reviews)` already done, as [`done_today`] counts them.
+    pub fn spill_forecast(
+        &self,
+        reviews: &mut [u64],
+        new_problems: &mut [u64],
+        (new_done, reviews_done): (usize, usize),
+    ) {
+        spill(reviews, self.max_reviews, reviews_done);
+        spill(new_problems, self.max_new, new_done);
+    }
+}
+
+fn spill(per_day: &mut [u64], cap: Option<usize>, done_today: usize) {
+    let Some(cap) = cap else {
+        return;
+    };
+    let mut carried = 0;
+    for (day, count) in per_day.iter_mut().enumerate() {
+        let room = if day == 0 {
+            cap.saturating_sub(done_today)
+        } else {
+            cap
+        };
+        carried += *count;
+        *count = carried.min(room as u64);
+        carried -= *count;
+    }
+}
+
+/// How many new problems and reviews were already rated on `today`. `reviews`
+/// must be grouped by problem in the order they happened, as
+/// [`db::load_reviews`] returns them. A rating given when adding a problem
+/// counts as neither, the one after introduces it and the rest are reviews.
+pub fn done_today(
+    reviews: &[Review],
+    added_unrated: &HashSet<i64>,
+    today: NaiveDate,
+) -> (usize, usize) {
+    let mut new_done = 0;
+    let mut reviews_done = 0;
+    let mut position = 0;
+    let mut previous = None;
+    for review in reviews {
+        if previous == Some(review.problem_id) {
+            position += 1;
+        } else {
+            position = 0;
+        }
+        previous = Some(review.problem_id);
+
+        if review.reviewed_at.date_naive() == today {
+            let introducing = introducing_rating(added_unrated, review.problem_id);
+            if position == introducing {
+                new_done += 1;
+            } else if position > introducing {
+                reviews_done += 1;
+            }
+        }
+    }
+    (new_done, reviews_done)
+}
+
+/// The due problems to show today, from the chosen deck if there is one.
+/// Reviews are taken most overdue first, new problems oldest first, each up to
+/// what is left of its daily cap; the rest stay due and lead the queue on the
+/// following days. The caps count everything rated today, in any deck.
+pub fn daily_queue(db: &Connection, today: NaiveDate) -> rusqlite::Result<DailyQueue> {
+    let limits = DailyLimits::load(db)?;
+    let review_counts = db::review_counts(db)?;
+    let added_unrated = db::added_unrated(db)?;
+    let (new_done, reviews_done) = done_today(&db::load_reviews(db)?, &added_unrated, today);
+    let scope = db::deck_scope(db)?;
+
+    let (mut new_problems, reviews): (Vec<_>, Vec<_>) = db::due_problems(db, today)?
+        .into_iter()
+        .filter(|(problem, _)| db::in_scope(&scope, problem.id))
+        .partition(|(problem, _)| is_new(&review_counts, &added_unrated, problem.id));
+    new_problems.sort_by(|(a, _), (b, _)| {
+        a.entry_date
+            .cmp(&b.entry_date)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    let due_count = new_problems.len() + reviews.len();
+    let review_room = limits
+        .max_reviews
+        .map_or(usize::MAX, |cap| cap.saturating_sub(reviews_done));
+    let new_room = limits
+        .max_new
+        .map_or(usize::MAX, |cap| cap.saturating_sub(new_done));
+
+    let mut problems: Vec<(Problem, i64)> = reviews
+        .into_iter()
+        .take(review_room)
+        .chain(new_problems.into_iter().take(new_room))
+        .collect();
+    problems.sort_by(|(a, a_overdue), (b, b_overdue)| {
+        b_overdue.cmp(a_overdue).then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(DailyQueue {
+        held_back: due_count - problems.len(),
+        problems,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::{ProblemMetadata, Rating};
+    use crate::migrations;
+    use chrono::{DateTime, Duration, Utc};
+
+    fn database() -> Connection {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        db
+    }
+
+    fn review(problem_id: i64, reviewed_at: DateTime<Utc>) -> Review {
+        Review {
+            problem_id,
+            rating: Rating::Hesitant,
+            reviewed_at,
+        }
+    }
+
+    #[test]
+    fn first_rating_introduces_a_problem_added_unrated() {
+        let now = Utc::now();
+        let added_unrated = HashSet::from([1]);
+        let reviews = [
+            review(1, now),
+            review(1, now),
+            review(2, now),
+            review(2, now),
+        ];
+        // Problem 1 is introduced then reviewed; problem 2 is added then
+        // introduced.
+        assert_eq!(
+            done_today(&reviews, &added_unrated, now.date_naive()),
+            (2, 1)
+        );
+    }
+
+    #[test]
+    fn the_forecast_leaves_out_what_was_done_today() {
+        let now = Utc::now();
+        let limits = DailyLimits {
+            max_new: Some(2),
+            max_reviews: Some(3),
+        };
+        // Problem 1 was added and introduced today, problems 2 and 3 were
+        // reviewed today.
+        let reviews = [
+            review(1, now),
+            review(1, now),
+            review(2, now - Duration::days(4)),
+            review(2, now - Duration::days(3)),
+            review(2, now),
+            review(3, now - Duration::days(4)),
+            review(3, now - Duration::days(3)),
+            review(3, now),
+        ];
+        let done = done_today(&reviews, &HashSet::new(), now.date_naive());
+        assert_eq!(done, (1, 2));
+
+        let mut due_reviews = [5, 0, 1];
+        let mut due_new = [2, 2, 0];
+        limits.spill_forecast(&mut due_reviews, &mut due_new, done);
+        assert_eq!(due_reviews, [1, 3, 2]);
+        assert_eq!(due_new, [1, 2, 1]);
+    }
+
+    #[test]
+    fn imported_problems_are_held_to_the_new_cap() {
+        let db = database();
+        db::set_setting(&db, "max_new_per_day", "2").unwrap();
+        let now = Utc::now();
+        let today = now.date_naive();
+        for i in 0..5 {
+            db::insert_unrated_problem(
+                &db,
+                &format!("Imported {i}"),
+                &ProblemMetadata::default(),
+                &now,
+            )
+            .unwrap();
+        }
+
+        let queue = daily_queue(&db, today).unwrap();
+        assert_eq!(queue.problems.len(), 2);
+        assert_eq!(queue.held_back, 3);
+
+        for (problem, _) in &queue.problems {
+            db::update_problem(&db, problem.id, Rating::Perfect, &now, None).unwrap();
+        }
+        let queue = daily_queue(&db, today).unwrap();
+        assert!(queue.problems.is_empty());
+        assert_eq!(queue.held_back, 3);
+    }
+}
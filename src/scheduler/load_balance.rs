@@ -0,0 +1,248 @@
+use crate::db;
+use crate::lib::ScheduleState;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+pub const DEFAULT_FUZZ_PERCENT: i64 = 5;
+pub const DEFAULT_MAX_SPREAD: i64 = 4;
+
+/// Intervals shorter than this only ever move one day later: pulling a 1 day
+/// review in to today would skip it, but a batch added together still spreads
+/// over two days.
+const MIN_BALANCED_INTERVAL: i64 = 3;
+
+/// Spreads due dates out so a batch of problems added together does not come
+/// due together. Every interval gets a tolerance window of `fuzz_percent` of
+/// its length (at least one day, at most `max_spread`) and the problem lands on
+/// the least loaded day in that window. Ties are broken by a hash of `seed`,
+/// the problem's id and the review day, so the same inputs always give the same
+/// date.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadBalancer {
+    pub enabled: bool,
+    pub fuzz_percent: i64,
+    pub max_spread: i64,
+    pub seed: u64,
+}
+
+impl Default for LoadBalancer {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fuzz_percent: DEFAULT_FUZZ_PERCENT,
+            max_spread: DEFAULT_MAX_SPREAD,
+            seed: 0,
+        }
+    }
+}
+
+pub fn parse_percent(value: &str) -> Option<i64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|percent| (0..=50).contains(percent))
+}
+
+pub fn parse_spread(value: &str) -> Option<i64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .filter(|days| (0..=30).contains(days))
+}
+
+pub fn parse_seed(value: &str) -> Option<u64> {
+    value.trim().parse::<u64>().ok()
+}
+
+impl LoadBalancer {
+    /// Reads `load_balance`, `fuzz_percent`, `fuzz_max_days` and `fuzz_seed`.
+    pub fn load(db: &Connection) -> rusqlite::Result<Self> {
+        let mut balancer = Self::default();
+        if let Some(enabled) = db::get_bool_setting(db, "load_balance")? {
+            balancer.enabled = enabled;
+        }
+        if let Some(value) = db::get_setting(db, "fuzz_percent")?
+            && let Some(percent) = parse_percent(&value)
+        {
+            balancer.fuzz_percent = percent;
+        }
+        if let Some(value) = db::get_setting(db, "fuzz_max_days")?
+            && let Some(spread) = parse_spread(&value)
+        {
+            balancer.max_spread = spread;
+        }
+        if let Some(value) = db::get_setting(db, "fuzz_seed")?
+            && let Some(seed) = parse_seed(&value)
+        {
+            balancer.seed = seed;
+        }
+        Ok(balancer)
+    }
+
+    /// Moves a freshly scheduled due date to the least loaded nearby day.
+    /// `loads` counts the problems already due on each date, not including
+    /// the problem being scheduled.
+    pub fn balance(
+        &self,
+        loads: &HashMap<NaiveDate, usize>,
+        problem_id: i64,
+        state: ScheduleState,
+        now: DateTime<Utc>,
+    ) -> (ScheduleState, DateTime<Utc>) {
+        let interval = state.interval;
+        if !self.enabled || self.max_spread == 0 || interval < 1 {
+            return (state, now + Duration::days(interval));
+        }
+        let candidates: Vec<i64> = if interval < MIN_BALANCED_INTERVAL {
+            (interval..=interval + 1).collect()
+        } else {
+            let spread = ((interval * self.fuzz_percent + 50) / 100).clamp(1, self.max_spread);
+            ((interval - spread).max(1)..=interval + spread).collect()
+        };
+        let load_of = |days: i64| {
+            let date = (now + Duration::days(days)).date_naive();
+            loads.get(&date).copied().unwrap_or(0)
+        };
+        let lightest = candidates
+            .iter()
+            .map(|&days| load_of(days))
+            .min()
+            .unwrap_or(0);
+        let ties: Vec<i64> = candidates
+            .into_iter()
+            .filter(|&days| load_of(days) == lightest)
+            .collect();
+
+        let pick = self.random(problem_id, now.date_naive()) % ties.len() as u64;
+        let interval = ties[pick as usize];
+        (
+            ScheduleState { interval, ..state },
+            now + Duration::days(interval),
+        )
+    }
+
+    /// Balances using the due dates currently stored in the database, leaving
+    /// out the problem's own.
+    pub fn apply(
+        &self,
+        db: &Connection,
+        problem_id: i64,
+        state: ScheduleState,
+        now: DateTime<Utc>,
+    ) -> rusqlite::Result<(ScheduleState, DateTime<Utc>)> {
+        if !self.enabled {
+            return Ok((state, now + Duration::days(state.interval)));
+        }
+        let mut loads = HashMap::new();
+        for problem in db::active_problems(db)? {
+            if problem.id != problem_id
+                && let Some(due_date) = db::due_date_of(&problem)
+            {
+                *loads.entry(due_date).or_insert(0) += 1;
+            }
+        }
+        Ok(self.balance(&loads, problem_id, state, now))
+    }
+
+    /// SplitMix64 over the seed, the problem id and the day, so reruns with
+    /// the same seed give the same result.
+    fn random(&self, problem_id: i64, day: NaiveDate) -> u64 {
+        let day = day.num_days_from_ce() as u64;
+        let id = (problem_id as u64).wrapping_mul(0xd6e8_feb8_6659_fd93);
+        let mut z = self.seed ^ id ^ day.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Rating;
+    use crate::migrations;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 18, 30, 0).unwrap()
+    }
+
+    fn state(interval: i64) -> ScheduleState {
+        ScheduleState {
+            interval,
+            ..ScheduleState::default()
+        }
+    }
+
+    fn balancer(seed: u64) -> LoadBalancer {
+        LoadBalancer {
+            max_spread: 3,
+            fuzz_percent: 20,
+            seed,
+            ..LoadBalancer::default()
+        }
+    }
+
+    /// Every day in the window equally busy, so only the tie-break decides.
+    fn even_loads() -> HashMap<NaiveDate, usize> {
+        (0..30)
+            .map(|days| ((now() + Duration::days(days)).date_naive(), 2))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_problem_and_day_give_the_same_date() {
+        let loads = even_loads();
+        let first = balancer(7).balance(&loads, 42, state(10), now());
+        let second = balancer(7).balance(&loads, 42, state(10), now());
+        assert_eq!(first.1, second.1);
+        assert_eq!(first.0.interval, second.0.interval);
+        assert!((8..=12).contains(&first.0.interval));
+    }
+
+    #[test]
+    fn picks_the_least_loaded_day_in_the_window() {
+        let mut loads = even_loads();
+        loads.insert((now() + Duration::days(11)).date_naive(), 0);
+        for seed in 0..20 {
+            let (state, due) = balancer(seed).balance(&loads, 42, state(10), now());
+            assert_eq!(state.interval, 11);
+            assert_eq!(due, now() + Duration::days(11));
+        }
+    }
+
+    #[test]
+    fn short_intervals_only_move_a_day_later() {
+        let mut loads = even_loads();
+        loads.insert((now() + Duration::days(1)).date_naive(), 0);
+        loads.insert((now() + Duration::days(3)).date_naive(), 0);
+        for seed in 0..20 {
+            let (moved, due) = balancer(seed).balance(&loads, 42, state(2), now());
+            assert_eq!(moved.interval, 3);
+            assert_eq!(due, now() + Duration::days(3));
+            let (moved, _) = balancer(seed).balance(&even_loads(), 42, state(1), now());
+            assert!((1..=2).contains(&moved.interval));
+        }
+    }
+
+    #[test]
+    fn apply_leaves_out_the_problems_own_due_date() {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        let mut ids = vec![];
+        for (name, days) in [("Own", 10), ("A", 8), ("B", 9), ("C", 11), ("D", 12)] {
+            db::insert_new_problem(&db, name, Rating::Hesitant, &now(), None).unwrap();
+            let id = db::find_problem_id(&db, name).unwrap().unwrap();
+            db::set_due_date(&db, id, &(now() + Duration::days(days))).unwrap();
+            ids.push(id);
+        }
+        for seed in 0..20 {
+            let (state, due) = balancer(seed).apply(&db, ids[0], state(10), now()).unwrap();
+            assert_eq!(state.interval, 10);
+            assert_eq!(due, now() + Duration::days(10));
+        }
+    }
+}
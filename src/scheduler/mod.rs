@@ -0,0 +1,26 @@
+pub mod fsrs;
+pub mod graduation;
+pub mod leitner;
+pub mod limits;
+pub mod load_balance;
+pub mod pause;
+pub mod simulate;
+pub mod sm2;
+pub mod snooze;
+
+use crate::db;
+use crate::lib::Scheduler;
+use rusqlite::Connection;
+
+/// Names accepted by the `scheduler` setting.
+pub const SCHEDULER_NAMES: &[&str] = &["sm2", "fsrs", "leitner"];
+
+/// Builds the scheduler configured for this database under the `scheduler`
+/// setting, defaulting to SM-2.
+pub fn load(db: &Connection) -> rusqlite::Result<Box<dyn Scheduler>> {
+    match db::get_setting(db, "scheduler")?.as_deref() {
+        Some("fsrs") => Ok(Box::new(fsrs::FsrsParams::load(db)?)),
+        Some("leitner") => Ok(Box::new(leitner::Leitner::load(db)?)),
+        _ => Ok(Box::new(sm2::Sm2)),
+    }
+}
@@ -0,0 +1,131 @@
+use crate::db;
+use crate::lib::Pause;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+
+impl Pause {
+    /// Number of days covered, counting both ends.
+    pub fn len_days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+
+    pub fn contains(&self, day: NaiveDate) -> bool {
+        self.start <= day && day <= self.end
+    }
+}
+
+pub fn is_paused(pauses: &[Pause], day: NaiveDate) -> bool {
+    pauses.iter().any(|pause| pause.contains(day))
+}
+
+/// Pushes a due date that falls inside a pause forward by the pause's length,
+/// keeping its offset from the pause start. Pauses are checked in order, so a
+/// date pushed into a later pause is pushed again.
+pub fn shift_past_pauses(pauses: &[Pause], due_date: DateTime<Utc>) -> DateTime<Utc> {
+    let mut due_date = due_date;
+    for pause in pauses {
+        if pause.contains(due_date.date_naive()) {
+            due_date += Duration::days(pause.len_days());
+        }
+    }
+    due_date
+}
+
+/// Stores a pause and rebases the schedule around it: every problem due on or
+/// after the pause start moves forward by the pause's length, so nothing comes
+/// due during it and the days after it are no busier than planned. Returns how
+/// many problems moved. The pause and every move are saved together or not
+/// at all.
+pub fn add_pause(db: &Connection, pause: &Pause) -> rusqlite::Result<usize> {
+    let transaction = db.unchecked_transaction()?;
+    db::insert_pause(&transaction, pause)?;
+
+    let shift = Duration::days(pause.len_days());
+    let mut moved = 0;
+    for problem in db::active_problems(&transaction)? {
+        let Some(due_date) = problem.due_date else {
+            continue;
+        };
+        if due_date.date_naive() >= pause.start {
+            db::set_due_date(&transaction, problem.id, &(due_date + shift))?;
+            moved += 1;
+        }
+    }
+    transaction.commit()?;
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Rating;
+    use crate::migrations;
+    use chrono::TimeZone;
+
+    fn day(month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, month, day).unwrap()
+    }
+
+    fn at(month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, 9, 0, 0).unwrap()
+    }
+
+    fn pause(start: NaiveDate, end: NaiveDate) -> Pause {
+        Pause { start, end }
+    }
+
+    #[test]
+    fn due_dates_inside_a_pause_move_past_it() {
+        let pauses = [pause(day(6, 10), day(6, 14))];
+        assert_eq!(shift_past_pauses(&pauses, at(6, 9)), at(6, 9));
+        assert_eq!(shift_past_pauses(&pauses, at(6, 10)), at(6, 15));
+        assert_eq!(shift_past_pauses(&pauses, at(6, 14)), at(6, 19));
+        assert_eq!(shift_past_pauses(&pauses, at(6, 15)), at(6, 15));
+    }
+
+    #[test]
+    fn a_date_pushed_into_a_later_pause_moves_again() {
+        let pauses = [pause(day(6, 10), day(6, 12)), pause(day(6, 14), day(6, 15))];
+        assert_eq!(shift_past_pauses(&pauses, at(6, 11)), at(6, 16));
+    }
+
+    #[test]
+    fn adding_a_pause_moves_active_problems_due_from_its_start() {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        let mut ids = vec![];
+        for (name, due) in [
+            ("Before", at(6, 9)),
+            ("During", at(6, 11)),
+            ("After", at(7, 1)),
+            ("Mastered", at(6, 11)),
+        ] {
+            db::insert_new_problem(&db, name, Rating::Hesitant, &at(6, 1), None).unwrap();
+            let id = db::find_problem_id(&db, name).unwrap().unwrap();
+            db::set_due_date(&db, id, &due).unwrap();
+            ids.push(id);
+        }
+        db.execute(
+            "UPDATE user_problems SET mastered = 1 WHERE id = ?1",
+            [ids[3]],
+        )
+        .unwrap();
+
+        let moved = add_pause(&db, &pause(day(6, 10), day(6, 14))).unwrap();
+        assert_eq!(moved, 2);
+        let due_dates: Vec<Option<DateTime<Utc>>> = ids
+            .iter()
+            .map(|id| db::load_problem(&db, *id).unwrap().unwrap().due_date)
+            .collect();
+        assert_eq!(
+            due_dates,
+            [
+                Some(at(6, 9)),
+                Some(at(6, 16)),
+                Some(at(7, 6)),
+                Some(at(6, 11))
+            ]
+        );
+        assert_eq!(db::load_pauses(&db).unwrap().len(), 1);
+    }
+}
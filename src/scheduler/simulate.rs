@@ -0,0 +1,256 @@
+use crate::db;
+use crate::lib::{Pause, Rating, Review, ScheduleState, Scheduler, SimulationResult};
+use crate::scheduler::fsrs::{self, FsrsParams};
+use crate::scheduler::leitner::Leitner;
+use crate::scheduler::load_balance::LoadBalancer;
+use crate::scheduler::pause;
+use crate::scheduler::sm2::Sm2;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+
+/// One scheduler setup to replay the history through.
+pub struct SimulationConfig {
+    pub name: String,
+    pub scheduler: Box<dyn Scheduler>,
+    /// State a problem starts from before its first rating.
+    pub initial: ScheduleState,
+}
+
+/// The configurations compared by default: what this database uses now, plus a
+/// spread of SM-2 starting eases, Leitner box layouts and FSRS retention
+/// targets.
+pub fn candidate_configs(db: &Connection) -> rusqlite::Result<Vec<SimulationConfig>> {
+    let fsrs_params = FsrsParams::load(db)?;
+    let scheduler_name = db::get_setting(db, "scheduler")?.unwrap_or_else(|| "sm2".to_string());
+
+    let mut configs = vec![SimulationConfig {
+        name: format!("current ({scheduler_name})"),
+        scheduler: super::load(db)?,
+        initial: ScheduleState::default(),
+    }];
+    for ease in [1.8, 2.5, 3.0] {
+        configs.push(SimulationConfig {
+            name: format!("SM-2, starting ease {ease:.1}"),
+            scheduler: Box::new(Sm2),
+            initial: ScheduleState {
+                ease_factor: ease,
+                ..ScheduleState::default()
+            },
+        });
+    }
+    for intervals in [vec![1, 2, 4, 8, 16], vec![1, 3, 7, 14, 30, 60]] {
+        configs.push(SimulationConfig {
+            name: format!("Leitner {}", join_days(&intervals)),
+            scheduler: Box::new(Leitner { intervals }),
+            initial: ScheduleState::default(),
+        });
+    }
+    for retention in [0.8, 0.9, 0.95] {
+        configs.push(SimulationConfig {
+            name: format!("FSRS, retention {retention}"),
+            scheduler: Box::new(FsrsParams {
+                retention,
+                ..fsrs_params.clone()
+            }),
+            initial: ScheduleState::default(),
+        });
+    }
+    Ok(configs)
+}
+
+fn join_days(intervals: &[i64]) -> String {
+    let days: Vec<String> = intervals.iter().map(|d| d.to_string()).collect();
+    days.join("/")
+}
+
+/// Splits reviews (grouped by problem, as [`db::load_reviews`] returns them)
+/// into one slice per problem.
+fn per_problem(reviews: &[Review]) -> Vec<&[Review]> {
+    reviews
+        .chunk_by(|a, b| a.problem_id == b.problem_id)
+        .collect()
+}
+
+/// Replays every problem's ratings through each configuration, then runs the
+/// schedule forward for `days`, assuming each problem keeps getting its last
+/// rating. Going forward, each rating is scheduled the way a real one is:
+/// load balanced against the other problems and moved past pauses. The replay
+/// of past ratings uses the scheduler alone, since how busy each day was back
+/// then isn't stored. Retention is judged by an FSRS memory model using
+/// `truth`, the weights saved by `fsrs-fit` or the defaults until it has been
+/// run, independently of the scheduler being simulated.
+pub fn simulate(
+    configs: &[SimulationConfig],
+    reviews: &[Review],
+    truth: &FsrsParams,
+    balancer: &LoadBalancer,
+    pauses: &[Pause],
+    now: DateTime<Utc>,
+    days: usize,
+) -> Vec<SimulationResult> {
+    let histories = per_problem(reviews);
+
+    // Memory stability per problem after its real history.
+    let memories: Vec<ScheduleState> = histories
+        .iter()
+        .map(|history| replay(truth, &ScheduleState::default(), history).0)
+        .collect();
+
+    configs
+        .iter()
+        .map(|config| {
+            let mut steady_per_day = 0.0;
+            let mut retention_total = 0.0;
+
+            // Per problem: its id, replayed state, due date and the rating it
+            // keeps getting.
+            let mut schedules: Vec<(i64, ScheduleState, DateTime<Utc>, Rating)> = vec![];
+            for (history, memory) in histories.iter().zip(&memories) {
+                let (state, due) = replay(config.scheduler.as_ref(), &config.initial, history);
+                let last_rating = history
+                    .last()
+                    .map_or(Rating::Difficult, |review| review.rating);
+
+                let interval = state.interval.max(1);
+                steady_per_day += 1.0 / interval as f64;
+                retention_total += fsrs::retrievability(interval as f64, memory.stability.max(0.1));
+                schedules.push((history[0].problem_id, state, due, last_rating));
+            }
+
+            let mut loads: HashMap<NaiveDate, usize> = HashMap::new();
+            for (_, _, due, _) in &schedules {
+                *loads.entry(due.date_naive()).or_insert(0) += 1;
+            }
+            let mut daily_reviews = vec![0; days];
+            for (day, count) in daily_reviews.iter_mut().enumerate() {
+                let date = now + Duration::days(day as i64);
+                for (problem_id, state, due, rating) in schedules.iter_mut() {
+                    if due.date_naive() > date.date_naive() {
+                        continue;
+                    }
+                    *count += 1;
+                    if let Some(load) = loads.get_mut(&due.date_naive()) {
+                        *load = load.saturating_sub(1);
+                    }
+                    let (next, _) = config.scheduler.schedule(state, *rating, date);
+                    let (next, next_due) = balancer.balance(&loads, *problem_id, next, date);
+                    *state = next;
+                    *due = pause::shift_past_pauses(pauses, next_due);
+                    *loads.entry(due.date_naive()).or_insert(0) += 1;
+                }
+            }
+
+            SimulationResult {
+                name: config.name.clone(),
+                daily_reviews,
+                steady_per_day,
+                expected_retention: if histories.is_empty() {
+                    0.0
+                } else {
+                    retention_total / histories.len() as f64
+                },
+            }
+        })
+        .collect()
+}
+
+/// Feeds one problem's ratings through a scheduler in order, returning the
+/// final state and due date.
+fn replay(
+    scheduler: &dyn Scheduler,
+    initial: &ScheduleState,
+    history: &[Review],
+) -> (ScheduleState, DateTime<Utc>) {
+    let mut state = *initial;
+    let mut due = history
+        .first()
+        .map_or_else(Utc::now, |review| review.reviewed_at);
+    for review in history {
+        (state, due) = scheduler.schedule(&state, review.rating, review.reviewed_at);
+    }
+    (state, due)
+}
+
+/// Runs every candidate configuration over the history of the problems that
+/// still come up for review, from the chosen deck if there is one, with this
+/// database's load balancing and pauses.
+pub fn run(db: &Connection, days: usize) -> rusqlite::Result<Vec<SimulationResult>> {
+    let configs = candidate_configs(db)?;
+    let scope = db::deck_scope(db)?;
+    let active: HashSet<i64> = db::active_problems(db)?
+        .into_iter()
+        .map(|problem| problem.id)
+        .filter(|id| db::in_scope(&scope, *id))
+        .collect();
+    let reviews: Vec<Review> = db::load_reviews(db)?
+        .into_iter()
+        .filter(|review| active.contains(&review.problem_id))
+        .collect();
+    let truth = FsrsParams::load(db)?;
+    let balancer = LoadBalancer::load(db)?;
+    let pauses = db::load_pauses(db)?;
+    Ok(simulate(
+        &configs,
+        &reviews,
+        &truth,
+        &balancer,
+        &pauses,
+        Utc::now(),
+        days,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::migrations;
+    use chrono::Duration;
+
+    fn database() -> Connection {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        db
+    }
+
+    /// Adds a problem rated a few times over the last weeks and returns its id.
+    fn add_reviewed(db: &Connection, name: &str) -> i64 {
+        let start = Utc::now() - Duration::days(30);
+        db::insert_new_problem(db, name, Rating::Hesitant, &start, None).unwrap();
+        let id = db::find_problem_id(db, name).unwrap().unwrap();
+        for days in [2, 6] {
+            db::update_problem(
+                db,
+                id,
+                Rating::Hesitant,
+                &(start + Duration::days(days)),
+                None,
+            )
+            .unwrap();
+        }
+        id
+    }
+
+    fn total_reviews(db: &Connection) -> u64 {
+        run(db, 60).unwrap()[0].daily_reviews.iter().sum()
+    }
+
+    #[test]
+    fn mastered_and_suspended_problems_are_left_out() {
+        let db = database();
+        add_reviewed(&db, "Two Sum");
+        let only_active = total_reviews(&db);
+        assert!(only_active > 0);
+
+        let mastered = add_reviewed(&db, "Valid Parentheses");
+        db.execute(
+            "UPDATE user_problems SET mastered = 1 WHERE id = ?1",
+            [mastered],
+        )
+        .unwrap();
+        let suspended = add_reviewed(&db, "Merge Intervals");
+        db::set_suspended(&db, suspended, true).unwrap();
+
+        assert_eq!(total_reviews(&db), only_active);
+    }
+}
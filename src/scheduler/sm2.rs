@@ -0,0 +1,150 @@
+use crate::lib::{Rating, ScheduleState, Scheduler};
+use chrono::{DateTime, Duration, Utc};
+
+const DEFAULT_EASE: f64 = 2.5;
+const MIN_EASE: f64 = 1.3;
+
+impl Default for ScheduleState {
+    fn default() -> Self {
+        Self {
+            ease_factor: DEFAULT_EASE,
+            interval: 0,
+            repetitions: 0,
+            stability: 0.0,
+            difficulty: 0.0,
+            leitner_box: 0,
+            last_review: None,
+        }
+    }
+}
+
+/// Classic SM-2: ratings below 3 count as a lapse and restart the repetitions,
+/// anything else grows the interval by the (adjusted) ease factor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sm2;
+
+impl Scheduler for Sm2 {
+    fn schedule(
+        &self,
+        state: &ScheduleState,
+        rating: Rating,
+        now: DateTime<Utc>,
+    ) -> (ScheduleState, DateTime<Utc>) {
+        let quality = rating.value() as f64;
+
+        let (interval, repetitions) = if quality < 3.0 {
+            (1, 0)
+        } else {
+            let interval = match state.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (state.interval as f64 * state.ease_factor).round() as i64,
+            };
+            (interval, state.repetitions + 1)
+        };
+
+        let ease_factor = (state.ease_factor
+            + (0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)))
+            .max(MIN_EASE);
+
+        let next = ScheduleState {
+            ease_factor,
+            interval,
+            repetitions,
+            last_review: Some(now),
+            ..*state
+        };
+        (next, now + Duration::days(interval))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 18, 30, 0).unwrap()
+    }
+
+    /// Rates a fresh problem with each rating in turn, returning the
+    /// (interval, repetitions, ease factor) after every one.
+    fn run(ratings: &[Rating]) -> Vec<(i64, i64, f64)> {
+        let mut state = ScheduleState::default();
+        ratings
+            .iter()
+            .map(|&rating| {
+                let (next, due) = Sm2.schedule(&state, rating, now());
+                assert_eq!(due, now() + Duration::days(next.interval));
+                state = next;
+                (state.interval, state.repetitions, state.ease_factor)
+            })
+            .collect()
+    }
+
+    fn assert_steps(ratings: &[Rating], expected: &[(i64, i64, f64)]) {
+        let steps = run(ratings);
+        assert_eq!(steps.len(), expected.len());
+        for (step, want) in steps.iter().zip(expected) {
+            assert_eq!((step.0, step.1), (want.0, want.1), "{ratings:?}");
+            assert!((step.2 - want.2).abs() < 1e-9, "{step:?} != {want:?}");
+        }
+    }
+
+    #[test]
+    fn first_rating_of_each_kind() {
+        for (rating, expected) in [
+            (Rating::Perfect, (1, 1, 2.6)),
+            (Rating::Hesitant, (1, 1, 2.5)),
+            (Rating::Difficult, (1, 1, 2.36)),
+            (Rating::Incorrect, (1, 0, 2.18)),
+            (Rating::Blackout, (1, 0, 1.96)),
+        ] {
+            assert_steps(&[rating], &[expected]);
+        }
+    }
+
+    #[test]
+    fn intervals_grow_by_the_ease_factor() {
+        assert_steps(
+            &[Rating::Hesitant; 4],
+            &[(1, 1, 2.5), (6, 2, 2.5), (15, 3, 2.5), (38, 4, 2.5)],
+        );
+        assert_steps(
+            &[Rating::Perfect; 4],
+            &[(1, 1, 2.6), (6, 2, 2.7), (16, 3, 2.8), (45, 4, 2.9)],
+        );
+    }
+
+    #[test]
+    fn ease_never_drops_below_the_floor() {
+        assert_steps(
+            &[Rating::Blackout; 4],
+            &[
+                (1, 0, 1.96),
+                (1, 0, 1.42),
+                (1, 0, MIN_EASE),
+                (1, 0, MIN_EASE),
+            ],
+        );
+    }
+
+    #[test]
+    fn ratings_below_difficult_restart_the_repetitions() {
+        let hesitant = [Rating::Hesitant; 3];
+        assert_steps(
+            &[&hesitant[..], &[Rating::Incorrect, Rating::Hesitant]].concat(),
+            &[
+                (1, 1, 2.5),
+                (6, 2, 2.5),
+                (15, 3, 2.5),
+                (1, 0, 2.18),
+                (1, 1, 2.18),
+            ],
+        );
+        assert_steps(
+            &[&hesitant[..], &[Rating::Difficult]].concat(),
+            &[(1, 1, 2.5), (6, 2, 2.5), (15, 3, 2.5), (38, 4, 2.36)],
+        );
+    }
+}
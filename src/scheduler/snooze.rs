@@ -0,0 +1,154 @@
+use crate::db;
+use crate::lib::DueOverride;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rusqlite::Connection;
+
+/// Longest snooze accepted in one go.
+const MAX_SNOOZE_DAYS: i64 = 365;
+
+/// Reads either a number of days to snooze by or a `YYYY-MM-DD` date to pin
+/// the problem to.
+pub fn parse_due_override(value: &str, today: NaiveDate) -> Result<DueOverride, String> {
+    let value = value.trim();
+    if let Ok(days) = value.parse::<i64>() {
+        return if (1..=MAX_SNOOZE_DAYS).contains(&days) {
+            Ok(DueOverride::Snooze(days))
+        } else {
+            Err(format!("snooze must be 1 to {MAX_SNOOZE_DAYS} days"))
+        };
+    }
+    match value.parse::<NaiveDate>() {
+        Ok(date) if date >= today => Ok(DueOverride::Pin(date)),
+        Ok(_) => Err("cannot pin to a day in the past".to_string()),
+        Err(_) => Err(format!(
+            "expected a number of days or a YYYY-MM-DD date, got {value:?}"
+        )),
+    }
+}
+
+impl DueOverride {
+    /// Name stored in the `kind` column of `due_overrides`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DueOverride::Snooze(_) => "snooze",
+            DueOverride::Pin(_) => "pin",
+        }
+    }
+
+    /// The new due date. A snooze counts from the current due date, or from
+    /// `now` if the problem is already overdue; a pin lands at the start of
+    /// the chosen day.
+    pub fn due_date(&self, current: Option<DateTime<Utc>>, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            DueOverride::Snooze(days) => current.unwrap_or(now).max(now) + Duration::days(*days),
+            DueOverride::Pin(date) => date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc(),
+        }
+    }
+}
+
+/// Moves a problem's due date without rating it and logs the change. Returns
+/// the new due date, or `None` if there is no such problem.
+pub fn apply_due_override(
+    db: &Connection,
+    problem_id: i64,
+    due_override: DueOverride,
+    now: DateTime<Utc>,
+) -> rusqlite::Result<Option<DateTime<Utc>>> {
+    let Some(problem) = db::load_problem(db, problem_id)? else {
+        return Ok(None);
+    };
+    let due_date = due_override.due_date(problem.due_date, now);
+
+    let transaction = db.unchecked_transaction()?;
+    db::set_due_date(&transaction, problem_id, &due_date)?;
+    db::record_due_override(
+        &transaction,
+        problem_id,
+        due_override.kind(),
+        problem.due_date.as_ref(),
+        &due_date,
+        &now,
+    )?;
+    transaction.commit()?;
+    Ok(Some(due_date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lib::Rating;
+    use crate::migrations;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 18, 30, 0).unwrap()
+    }
+
+    fn today() -> NaiveDate {
+        now().date_naive()
+    }
+
+    #[test]
+    fn accepts_days_and_dates() {
+        assert_eq!(
+            parse_due_override(" 3 ", today()),
+            Ok(DueOverride::Snooze(3))
+        );
+        assert_eq!(
+            parse_due_override("365", today()),
+            Ok(DueOverride::Snooze(365))
+        );
+        assert_eq!(
+            parse_due_override("2024-05-20", today()),
+            Ok(DueOverride::Pin(
+                NaiveDate::from_ymd_opt(2024, 5, 20).unwrap()
+            ))
+        );
+        assert_eq!(
+            parse_due_override("2024-05-01", today()),
+            Ok(DueOverride::Pin(today()))
+        );
+    }
+
+    #[test]
+    fn rejects_past_dates_and_garbage() {
+        for value in ["2024-04-30", "0", "-2", "366", "tomorrow", "2024-13-01", ""] {
+            assert!(parse_due_override(value, today()).is_err(), "{value:?}");
+        }
+    }
+
+    #[test]
+    fn applying_an_override_moves_the_due_date_and_logs_it() {
+        let mut db = Connection::open_in_memory().unwrap();
+        migrations::run(&mut db).unwrap();
+        db::insert_new_problem(&db, "Two Sum", Rating::Hesitant, &now(), None).unwrap();
+        let id = db::find_problem_id(&db, "Two Sum").unwrap().unwrap();
+        let due = now() + Duration::days(2);
+        db::set_due_date(&db, id, &due).unwrap();
+
+        let moved = apply_due_override(&db, id, DueOverride::Snooze(3), now()).unwrap();
+        assert_eq!(moved, Some(due + Duration::days(3)));
+        assert_eq!(db::load_problem(&db, id).unwrap().unwrap().due_date, moved);
+
+        let logged: (i64, String, Option<String>, String) = db
+            .query_row(
+                "SELECT problem_id, kind, previous_due, new_due FROM due_overrides",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+            )
+            .unwrap();
+        assert_eq!(
+            logged,
+            (
+                id,
+                "snooze".to_string(),
+                Some(db::timestamp(&due)),
+                db::timestamp(&(due + Duration::days(3)))
+            )
+        );
+        assert_eq!(
+            apply_due_override(&db, id + 1, DueOverride::Snooze(3), now()).unwrap(),
+            None
+        );
+    }
+}
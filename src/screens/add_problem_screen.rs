@@ -1,6 +1,10 @@
-use crate::lib::{Action, AddProblemScreen, InputSelector, Problem, View};
+use crate::db;
+use crate::lib::{
+    Action, AddProblemScreen, Difficulty, InputSelector, ProblemMetadata, Rating, Stopwatch, View,
+};
+use crate::stopwatch::format_duration;
 use chrono::prelude::*;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Flex, Rect};
@@ -12,10 +16,10 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::Borders;
 use ratatui::widgets::Padding;
 use ratatui::widgets::Widget;
+use ratatui::widgets::Wrap;
 use ratatui::widgets::{Block, Paragraph};
-use std::io;
 use std::sync::Arc;
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
 
 impl<'a> AddProblemScreen<'a> {
     /// This is your "constructor"
@@ -29,7 +33,6 @@ impl<'a> AddProblemScreen<'a> {
         let failed_to_add_problem = false;
         let sucessfully_updated_problem = false;
         let incorrect_name = false;
-        let entry_date = Utc::now().to_string();
 
         // Setup the textarea appearance ONCE here
         problem_name.set_block(
@@ -49,11 +52,33 @@ impl<'a> AddProblemScreen<'a> {
 
         problem_rating.set_cursor_line_style(Style::default().add_modifier(Modifier::UNDERLINED));
 
+        let problem_platform = optional_field(" Platform ");
+        let problem_number = optional_field(" Number / Slug ");
+        let problem_difficulty = optional_field(" Easy / Medium / Hard ");
+        let problem_url = optional_field(" URL ");
+        let problem_tags = optional_field(TAGS_TITLE);
+        let problem_language = optional_field(" Language ");
+        let mut problem_solution = optional_field(" Solution code, Enter for a new line ");
+        problem_solution.set_line_number_style(Style::default().fg(Color::DarkGray));
+        let known_tags = db::load_tags(&db)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+
         Self {
             problem_name,
-            entry_date,
             problem_rating,
+            problem_platform,
+            problem_number,
+            problem_difficulty,
+            problem_url,
+            problem_tags,
+            known_tags,
+            problem_language,
+            problem_solution,
             incorrect_name,
+            incorrect_difficulty: false,
             incorrect_rating,
             input_mode,
             db,
@@ -61,8 +86,82 @@ impl<'a> AddProblemScreen<'a> {
             successful_problem_added,
             failed_to_add_problem,
             sucessfully_updated_problem,
+            message: None,
+            stopwatch: Stopwatch::default(),
+        }
+    }
+
+    /// What was typed into the optional fields; blank ones stay unset so an
+    /// update doesn't wipe details entered earlier.
+    fn metadata(&self) -> ProblemMetadata {
+        ProblemMetadata {
+            url: field_value(&self.problem_url),
+            platform: field_value(&self.problem_platform),
+            number: field_value(&self.problem_number),
+            difficulty: field_value(&self.problem_difficulty)
+                .and_then(|difficulty| Difficulty::parse(&difficulty)),
+        }
+    }
+
+    /// Stores the optional fields. Typed tags replace the problem's tags;
+    /// leaving the field blank keeps them. Code goes with the attempt just
+    /// recorded.
+    fn save_details(&self, problem_name: &str) -> rusqlite::Result<()> {
+        if let Some(id) = db::find_problem_id(&self.db, problem_name)? {
+            db::set_metadata(&self.db, id, &self.metadata())?;
+            if let Some(tags) = field_value(&self.problem_tags) {
+                let tags: Vec<String> = tags.split(',').map(str::to_string).collect();
+                db::set_problem_tags(&self.db, id, &tags)?;
+            }
+            let code = self.problem_solution.lines().join("\n");
+            if !code.trim().is_empty() {
+                let language = field_value(&self.problem_language);
+                db::set_solution(&self.db, id, language.as_deref(), code.trim_end())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// A known tag that completes the one being typed, if any.
+    fn tag_suggestion(&self) -> Option<&str> {
+        let typed = self.problem_tags.lines().join(" ");
+        let fragment = typed.rsplit(',').next()?.trim_start().to_lowercase();
+        if fragment.is_empty() {
+            return None;
         }
+        self.known_tags
+            .iter()
+            .find(|tag| tag.to_lowercase().starts_with(&fragment) && tag.len() > fragment.len())
+            .map(String::as_str)
     }
+
+    /// Replaces the tag being typed with `suggestion`, ready for the next one.
+    fn complete_tag(&mut self, suggestion: &str) {
+        let typed = self.problem_tags.lines().join(" ");
+        let done = typed.rsplit_once(',').map_or("", |(done, _)| done);
+        let completed = if done.is_empty() {
+            format!("{suggestion}, ")
+        } else {
+            format!("{done}, {suggestion}, ")
+        };
+        self.problem_tags.move_cursor(CursorMove::End);
+        self.problem_tags.delete_line_by_head();
+        self.problem_tags.insert_str(completed);
+    }
+}
+
+const TAGS_TITLE: &str = " Tags, comma separated ";
+
+fn optional_field<'a>(title: &'a str) -> TextArea<'a> {
+    let mut field = TextArea::default();
+    field.set_block(Block::default().borders(Borders::ALL).title(title));
+    field.set_cursor_line_style(Style::default().add_modifier(Modifier::UNDERLINED));
+    field
+}
+
+fn field_value(field: &TextArea) -> Option<String> {
+    let value = field.lines().join(" ").trim().to_string();
+    (!value.is_empty()).then_some(value)
 }
 
 impl<'a> View for AddProblemScreen<'a> {
@@ -70,7 +169,17 @@ impl<'a> View for AddProblemScreen<'a> {
         frame.render_widget(self, frame.area());
     }
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Action {
+        if key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && key_event.code == KeyCode::Char('t')
+        {
+            self.stopwatch.toggle();
+            return Action::NoOp;
+        }
         match key_event.code {
+            KeyCode::Enter if !self.confirm_popup && self.input_mode == InputSelector::Solution => {
+                self.problem_solution.insert_newline();
+                Action::NoOp
+            }
             KeyCode::Enter => {
                 self.confirm_popup = !self.confirm_popup;
                 Action::NoOp
@@ -79,11 +188,28 @@ impl<'a> View for AddProblemScreen<'a> {
                 self.confirm_popup = false;
                 Action::ShouldSwitch
             }
+            KeyCode::Tab
+                if !self.confirm_popup
+                    && self.input_mode == InputSelector::Tags
+                    && self.tag_suggestion().is_some() =>
+            {
+                if let Some(suggestion) = self.tag_suggestion().map(str::to_string) {
+                    self.complete_tag(&suggestion);
+                }
+                Action::NoOp
+            }
             KeyCode::Tab => {
                 // Switch focus on Tab press
                 self.input_mode = match self.input_mode {
                     InputSelector::ProblemName => InputSelector::ProblemRating,
-                    InputSelector::ProblemRating => InputSelector::ProblemName,
+                    InputSelector::ProblemRating => InputSelector::Platform,
+                    InputSelector::Platform => InputSelector::Number,
+                    InputSelector::Number => InputSelector::Difficulty,
+                    InputSelector::Difficulty => InputSelector::Url,
+                    InputSelector::Url => InputSelector::Tags,
+                    InputSelector::Tags => InputSelector::Language,
+                    InputSelector::Language => InputSelector::Solution,
+                    InputSelector::Solution => InputSelector::ProblemName,
                 };
                 Action::NoOp
             }
@@ -92,51 +218,85 @@ impl<'a> View for AddProblemScreen<'a> {
                     match key_event.code {
                         KeyCode::Char('A') => {
                             let problem_name: String = self.problem_name.lines().join("\n");
-                            let problem_rating: String = self.problem_rating.lines().join("\n");
-                            if self.incorrect_rating || self.incorrect_name {
-                                self.failed_to_add_problem = true;
-                            } else {
-                                match insert_new_problem(
+                            let problem_rating =
+                                Rating::parse(&self.problem_rating.lines().join(""));
+                            if let Some(rating) = problem_rating
+                                && !self.incorrect_name
+                                && !self.incorrect_difficulty
+                            {
+                                match db::insert_new_problem(
                                     &self.db,
                                     &problem_name,
-                                    &problem_rating,
-                                    &self.entry_date,
-                                ) {
+                                    rating,
+                                    &Utc::now(),
+                                    self.stopwatch.seconds(),
+                                )
+                                .and_then(|added| {
+                                    if added {
+                                        self.save_details(&problem_name)?;
+                                    }
+                                    Ok(added)
+                                }) {
                                     Ok(true) => {
                                         self.successful_problem_added = true;
+                                        self.stopwatch.reset();
                                     }
                                     Ok(false) => {
                                         self.failed_to_add_problem = true;
                                     }
                                     Err(error) => {
-                                        println!("There was an error adding problem: {:#?}", error);
+                                        self.failed_to_add_problem = true;
+                                        self.message = Some(format!(
+                                            "There was an error adding {problem_name}: {error}"
+                                        ));
                                     }
                                 }
+                            } else {
+                                self.failed_to_add_problem = true;
                             }
                         }
                         KeyCode::Char('U') => {
                             let problem_name: String = self.problem_name.lines().join("\n");
 
-                            let problem_rating: String = self.problem_rating.lines().join("\n");
-                            if self.incorrect_rating {
-                                self.failed_to_add_problem = true;
-                            } else {
-                                match update_problem(
-                                    &self.db,
-                                    &problem_name,
-                                    &problem_rating,
-                                    &self.entry_date,
-                                ) {
+                            let problem_rating =
+                                Rating::parse(&self.problem_rating.lines().join(""));
+                            if let Some(rating) = problem_rating
+                                && !self.incorrect_difficulty
+                            {
+                                let updated = db::find_problem_id(&self.db, &problem_name)
+                                    .and_then(|id| match id {
+                                        Some(id) => db::update_problem(
+                                            &self.db,
+                                            id,
+                                            rating,
+                                            &Utc::now(),
+                                            self.stopwatch.seconds(),
+                                        ),
+                                        None => Ok(false),
+                                    })
+                                    .and_then(|updated| {
+                                        if updated {
+                                            self.save_details(&problem_name)?;
+                                        }
+                                        Ok(updated)
+                                    });
+                                match updated {
                                     Ok(true) => {
                                         self.sucessfully_updated_problem = true;
+                                        self.stopwatch.reset();
                                     }
                                     Ok(false) => {
                                         self.failed_to_add_problem = true;
                                     }
                                     Err(error) => {
-                                        println!("There was an error adding problem: {:#?}", error);
+                                        self.failed_to_add_problem = true;
+                                        self.message = Some(format!(
+                                            "There was an error updating {problem_name}: {error}"
+                                        ));
                                     }
                                 }
+                            } else {
+                                self.failed_to_add_problem = true;
                             }
                         }
                         KeyCode::Esc => {
@@ -144,12 +304,14 @@ impl<'a> View for AddProblemScreen<'a> {
                             self.successful_problem_added = false;
                             self.failed_to_add_problem = false;
                             self.sucessfully_updated_problem = false;
+                            self.message = None;
                         }
                         _ => {
                             self.confirm_popup = false;
                             self.successful_problem_added = false;
                             self.failed_to_add_problem = false;
                             self.sucessfully_updated_problem = false;
+                            self.message = None;
                         }
                     }
                 } else {
@@ -197,8 +359,8 @@ impl<'a> View for AddProblemScreen<'a> {
                                         .border_style(Style::default().fg(Color::Red)),
                                 );
                             } else {
-                                match trimmed.parse::<i8>() {
-                                    Ok(val) if (1..=5).contains(&val) => {
+                                match Rating::parse(trimmed) {
+                                    Some(_) => {
                                         self.incorrect_rating = false;
                                         self.problem_rating.set_block(
                                             Block::default()
@@ -221,6 +383,29 @@ impl<'a> View for AddProblemScreen<'a> {
                                 }
                             }
                         }
+                        InputSelector::Platform => {
+                            self.problem_platform.input(key_event);
+                        }
+                        InputSelector::Number => {
+                            self.problem_number.input(key_event);
+                        }
+                        InputSelector::Url => {
+                            self.problem_url.input(key_event);
+                        }
+                        InputSelector::Tags => {
+                            self.problem_tags.input(key_event);
+                        }
+                        InputSelector::Language => {
+                            self.problem_language.input(key_event);
+                        }
+                        InputSelector::Solution => {
+                            self.problem_solution.input(key_event);
+                        }
+                        InputSelector::Difficulty => {
+                            self.problem_difficulty.input(key_event);
+                            self.incorrect_difficulty = field_value(&self.problem_difficulty)
+                                .is_some_and(|value| Difficulty::parse(&value).is_none());
+                        }
                     }
                 }
                 Action::NoOp
@@ -263,7 +448,10 @@ impl<'a> Widget for &AddProblemScreen<'a> {
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints([
-                    Constraint::Length(1),
+                    Constraint::Length(2),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
+                    Constraint::Length(3),
                     Constraint::Length(3),
                     Constraint::Min(0),
                 ])
@@ -273,13 +461,39 @@ impl<'a> Widget for &AddProblemScreen<'a> {
                 .direction(Direction::Horizontal)
                 .constraints([Constraint::Percentage(80), Constraint::Percentage(20)])
                 .split(chunks[1]); // We split the middle vertical chunk
-            //
-
-            let header_text = "Here you can add / update a problem with the rating: 1-5";
-            Paragraph::new(header_text)
-                .centered()
-                .style(Style::default().fg(Color::Red))
-                .render(chunks[0], buf);
+            let metadata_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(40),
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(30),
+                ])
+                .split(chunks[2]);
+
+            let header_text = "Here you can add / update a problem with the rating: 1-5, Tab moves between fields";
+            let timer = format!(
+                "⏱ {} {}",
+                format_duration(self.stopwatch.elapsed().as_secs() as i64),
+                if self.stopwatch.is_running() {
+                    "(Ctrl+T pauses, rating stops it)"
+                } else {
+                    "(Ctrl+T starts timing the attempt)"
+                }
+            );
+            Paragraph::new(vec![
+                Line::from(header_text),
+                Line::styled(
+                    timer,
+                    if self.stopwatch.is_running() {
+                        Style::default().fg(Color::LightGreen)
+                    } else {
+                        Style::default().fg(Color::DarkGray)
+                    },
+                ),
+            ])
+            .centered()
+            .style(Style::default().fg(Color::Red))
+            .render(chunks[0], buf);
 
             if self.incorrect_name {
                 let mut incorrect_name = self.problem_name.clone();
@@ -310,7 +524,45 @@ impl<'a> Widget for &AddProblemScreen<'a> {
                 self.problem_rating.render(input_chunks[1], buf);
             }
 
-            if self.incorrect_rating {}
+            self.problem_platform.render(metadata_chunks[0], buf);
+            self.problem_number.render(metadata_chunks[1], buf);
+            if self.incorrect_difficulty {
+                let mut incorrect_difficulty = self.problem_difficulty.clone();
+                incorrect_difficulty.set_block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title(" Invalid! (Easy / Medium / Hard) ")
+                        .border_style(Style::default().fg(Color::Red)),
+                );
+                incorrect_difficulty.set_style(Style::default().fg(Color::Red));
+                incorrect_difficulty.render(metadata_chunks[2], buf);
+            } else {
+                self.problem_difficulty.render(metadata_chunks[2], buf);
+            }
+            self.problem_url.render(chunks[3], buf);
+            match self.tag_suggestion() {
+                Some(suggestion) if self.input_mode == InputSelector::Tags => {
+                    let mut tags = self.problem_tags.clone();
+                    tags.set_block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(format!("{TAGS_TITLE}- Tab completes \"{suggestion}\" ")),
+                    );
+                    tags.render(chunks[4], buf);
+                }
+                _ => self.problem_tags.render(chunks[4], buf),
+            }
+
+            let solution_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(20), Constraint::Percentage(80)])
+                .split(chunks[5]);
+            let language_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(0)])
+                .split(solution_chunks[0]);
+            self.problem_language.render(language_chunks[0], buf);
+            self.problem_solution.render(solution_chunks[1], buf);
         } else {
             let instructions = Line::from(vec![
                 " Back ".into(),
@@ -351,10 +603,15 @@ impl<'a> Widget for &AddProblemScreen<'a> {
                     .style(Style::default().fg(Color::LightGreen))
                     .render(inner_popup_area, buf);
             } else if self.failed_to_add_problem {
-                Paragraph::new("Could not add / update problem")
-                    .centered()
-                    .style(Style::default().fg(Color::LightRed))
-                    .render(inner_popup_area, buf);
+                Paragraph::new(
+                    self.message
+                        .as_deref()
+                        .unwrap_or("Could not add / update problem"),
+                )
+                .wrap(Wrap { trim: true })
+                .centered()
+                .style(Style::default().fg(Color::LightRed))
+                .render(inner_popup_area, buf);
             } else if self.sucessfully_updated_problem {
                 Paragraph::new("Successfully Updated Problem")
                     .centered()
@@ -372,88 +629,3 @@ fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let [area] = horizontal.areas(area);
     area
 }
-
-fn check_table(db: &Arc<rusqlite::Connection>, table_name: &str) -> rusqlite::Result<bool> {
-    db.table_exists(None, table_name)
-}
-
-fn check_row_exists(db: &Arc<rusqlite::Connection>, problem_name: &str) -> rusqlite::Result<bool> {
-    let query = "SELECT EXISTS(SELECT 1 FROM user_problems WHERE problem_name = ?1)";
-    let exists = db.query_row(query, [problem_name], |row| row.get(0))?;
-
-    Ok(exists)
-}
-
-fn create_table(db: &Arc<rusqlite::Connection>) -> rusqlite::Result<()> {
-    db.execute(
-        "CREATE TABLE IF NOT EXISTS user_problems (
-                problem_name TEXT NOT NULL,
-                problem_rating TEXT NOT NULL,
-                entry_date TEXT NOT NULL
-            )",
-        (),
-    )?;
-    Ok(())
-}
-
-fn update_problem(
-    db: &Arc<rusqlite::Connection>,
-    problem_name: &String,
-    problem_rating: &String,
-    entry_date: &String,
-) -> rusqlite::Result<bool> {
-    // Changed return type
-    let table_name = "user_problems";
-
-    // Ensure table exists
-    if !check_table(db, table_name).unwrap_or(false) {
-        create_table(db)?;
-    }
-
-    if check_row_exists(db, problem_name).unwrap() {
-        let problem = Problem {
-            name: problem_name.to_string(),
-            rating: problem_rating.to_string(),
-            entry_date: entry_date.to_string(),
-        };
-
-        db.execute(
-            "UPDATE user_problems SET problem_rating=?1, entry_date = ?2 WHERE problem_name = ?3",
-            (&problem.rating, &problem.entry_date, &problem.name),
-        )?;
-        return Ok(true);
-    }
-
-    Ok(false)
-}
-
-fn insert_new_problem(
-    db: &Arc<rusqlite::Connection>,
-    problem_name: &String,
-    problem_rating: &String,
-    entry_date: &String,
-) -> rusqlite::Result<bool> {
-    // Changed return type
-    let table_name = "user_problems";
-
-    // Ensure table exists
-    if !check_table(db, table_name).unwrap() {
-        create_table(db)?;
-    }
-
-    // Check if row exists
-    if !check_row_exists(db, problem_name).unwrap() {
-        let problem = Problem {
-            name: problem_name.to_string(),
-            rating: problem_rating.to_string(),
-            entry_date: entry_date.to_string(),
-        };
-        db.execute(
-            "INSERT INTO user_problems (problem_name, problem_rating, entry_date) VALUES (?1, ?2, ?3)",
-            (&problem.name, &problem.rating, &problem.entry_date),
-        )?;
-        return Ok(true); // Signifies a new row was added
-    }
-
-    Ok(false) // Signifies nothing was added, but no error occurred
-}
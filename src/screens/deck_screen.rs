@@ -0,0 +1,395 @@
+use crate::db;
+use crate::lib::{Action, Deck, DeckPrompt, DeckScreen, ScreenAction, View};
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Widget};
+use std::sync::Arc;
+
+impl<'a> DeckScreen<'a> {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
+        let mut list_state = TableState::default();
+        list_state.select(Some(0));
+        let decks = db::load_decks(&db).unwrap_or_default();
+        let items = deck_items(&decks);
+        Self {
+            db,
+            decks,
+            items,
+            list_state,
+            editing: None,
+            members: vec![],
+            prompt: None,
+            confirm_delete: false,
+            message: None,
+        }
+    }
+
+    fn selected(&self) -> Option<&Deck> {
+        self.decks.get(self.list_state.selected()?)
+    }
+
+    /// Re-reads the decks, keeping the selection on `deck_id` if it is given
+    /// and still there.
+    fn reload(&mut self, deck_id: Option<i64>) {
+        if let Ok(decks) = db::load_decks(&self.db) {
+            self.items = deck_items(&decks);
+            self.decks = decks;
+        }
+        let selected = deck_id
+            .and_then(|deck_id| self.decks.iter().position(|deck| deck.id == deck_id))
+            .or(self.list_state.selected())
+            .unwrap_or(0);
+        self.list_state
+            .select(Some(selected.min(self.items.len().saturating_sub(1))));
+    }
+
+    /// Switches the list over to every problem, to pick the selected deck's.
+    fn edit_members(&mut self) {
+        let Some(deck) = self.selected().cloned() else {
+            return;
+        };
+        match db::deck_candidates(&self.db, deck.id) {
+            Ok(members) => {
+                self.items = member_items(&members);
+                self.members = members;
+                self.editing = Some(deck);
+                self.list_state.select(Some(0));
+                self.message = None;
+            }
+            Err(error) => {
+                self.message = Some(format!("There was an error loading the problems: {error}"))
+            }
+        }
+    }
+
+    fn stop_editing(&mut self) {
+        let deck_id = self.editing.take().map(|deck| deck.id);
+        self.members.clear();
+        self.reload(deck_id);
+    }
+
+    /// Puts the selected problem in the deck being edited, or takes it out.
+    fn toggle_member(&mut self) {
+        let Some(deck) = &self.editing else {
+            return;
+        };
+        let Some(index) = self.list_state.selected() else {
+            return;
+        };
+        let Some((problem_id, name, member)) = self.members.get_mut(index) else {
+            return;
+        };
+        let member_now = !*member;
+        match db::set_deck_member(&self.db, deck.id, *problem_id, member_now) {
+            Ok(()) => {
+                *member = member_now;
+                self.message = None;
+            }
+            Err(error) => {
+                self.message = Some(format!("There was an error changing {name}: {error}"));
+            }
+        }
+        self.items = member_items(&self.members);
+    }
+
+    fn apply_prompt(&mut self, prompt: DeckPrompt, input: &str) {
+        let name = db::normalize_name(input);
+        let selected = self.selected().cloned();
+        let mut deck_id = selected.as_ref().map(|deck| deck.id);
+        self.message = match (prompt, selected) {
+            (DeckPrompt::Create | DeckPrompt::Rename, _) if name.is_empty() => {
+                Some("A deck needs a name".to_string())
+            }
+            (DeckPrompt::Create, _) => match db::create_deck(&self.db, &name, "") {
+                Ok(Some(id)) => {
+                    deck_id = Some(id);
+                    Some(format!("Created {name}, press Enter to add problems to it"))
+                }
+                Ok(None) => Some(format!("There is already a deck called {name}")),
+                Err(error) => Some(format!("There was an error creating {name}: {error}")),
+            },
+            (DeckPrompt::Rename, Some(deck)) => match db::rename_deck(&self.db, deck.id, &name) {
+                Ok(true) => Some(format!("Renamed {} to {name}", deck.name)),
+                Ok(false) => Some(format!("There is already a deck called {name}")),
+                Err(error) => Some(format!(
+                    "There was an error renaming {}: {error}",
+                    deck.name
+                )),
+            },
+            (DeckPrompt::Describe, Some(deck)) => {
+                match db::set_deck_description(&self.db, deck.id, input) {
+                    Ok(()) => None,
+                    Err(error) => Some(format!(
+                        "There was an error describing {}: {error}",
+                        deck.name
+                    )),
+                }
+            }
+            (_, None) => None,
+        };
+        self.reload(deck_id);
+    }
+
+    fn delete_selected(&mut self) {
+        let Some(deck) = self.selected().cloned() else {
+            return;
+        };
+        self.message = match db::delete_deck(&self.db, deck.id) {
+            Ok(()) => Some(format!("Deleted {}, its problems are kept", deck.name)),
+            Err(error) => Some(format!(
+                "There was an error deleting {}: {error}",
+                deck.name
+            )),
+        };
+        self.reload(None);
+    }
+}
+
+/// A screen title naming the deck the screen is scoped to, if any.
+pub fn scoped_title(title: &str, deck: Option<&Deck>) -> Line<'static> {
+    match deck {
+        Some(deck) => Line::from(format!("{} · {} ", title.trim_end(), deck.name)),
+        None => Line::from(title.to_string()),
+    }
+}
+
+fn deck_items(decks: &[Deck]) -> Vec<Row<'static>> {
+    decks
+        .iter()
+        .map(|deck| {
+            Row::new(vec![
+                Cell::from(deck.name.clone()),
+                Cell::from(deck.problems.to_string()),
+                Cell::from(deck.description.clone()),
+            ])
+        })
+        .collect()
+}
+
+fn member_items(members: &[(i64, String, bool)]) -> Vec<Row<'static>> {
+    members
+        .iter()
+        .map(|(_, name, member)| {
+            let (mark, style) = if *member {
+                ("[x]", Style::default().fg(Color::LightGreen))
+            } else {
+                ("[ ]", Style::default())
+            };
+            Row::new(vec![
+                Cell::from(mark).style(style),
+                Cell::from(name.clone()),
+            ])
+        })
+        .collect()
+}
+
+impl<'a> View for DeckScreen<'a> {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        if let Some((prompt, input)) = self.prompt.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let prompt = *prompt;
+                    let input = std::mem::take(input);
+                    self.prompt = None;
+                    self.apply_prompt(prompt, &input);
+                }
+                KeyCode::Esc => self.prompt = None,
+                _ => {}
+            }
+            return Action::NoOp;
+        }
+
+        if self.editing.is_some() {
+            return match key_event.code {
+                KeyCode::Char('q') => Action::Quit, // Global
+                KeyCode::Esc => {
+                    self.stop_editing();
+                    Action::NoOp
+                }
+                KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
+                KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
+                KeyCode::Char(' ') | KeyCode::Enter => {
+                    self.toggle_member();
+                    Action::NoOp
+                }
+                _ => Action::NoOp,
+            };
+        }
+
+        if key_event.code != KeyCode::Char('d') {
+            self.confirm_delete = false;
+        }
+        match key_event.code {
+            KeyCode::Char('q') => Action::Quit, // Global
+            KeyCode::Esc => Action::ShouldSwitch,
+            KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
+            KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
+            KeyCode::Char('n') => {
+                self.prompt = Some((DeckPrompt::Create, String::new()));
+                self.message = None;
+                Action::NoOp
+            }
+            KeyCode::Char('r') => {
+                if let Some(deck) = self.selected() {
+                    self.prompt = Some((DeckPrompt::Rename, deck.name.clone()));
+                    self.message = None;
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('e') => {
+                if let Some(deck) = self.selected() {
+                    self.prompt = Some((DeckPrompt::Describe, deck.description.clone()));
+                    self.message = None;
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('d') if self.confirm_delete => {
+                self.confirm_delete = false;
+                self.delete_selected();
+                Action::NoOp
+            }
+            KeyCode::Char('d') => {
+                self.confirm_delete = self.selected().is_some();
+                self.message = None;
+                Action::NoOp
+            }
+            KeyCode::Enter => {
+                self.edit_members();
+                Action::NoOp
+            }
+            _ => Action::NoOp,
+        }
+    }
+}
+
+impl<'a> Widget for &DeckScreen<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let key_style = Style::default()
+            .fg(Color::Blue)
+            .add_modifier(Modifier::BOLD);
+        let mut instructions = Line::from(vec![
+            " Quit ".into(),
+            Span::styled("<Q>", key_style),
+            " Scroll ".into(),
+            Span::styled("<↑/↓>", key_style),
+        ]);
+        if self.editing.is_some() {
+            instructions.push_span(" Add / Remove ");
+            instructions.push_span(Span::styled("<Space>", key_style));
+            instructions.push_span(" Done ");
+        } else {
+            for (label, key) in [
+                (" New ", "<N>"),
+                (" Rename ", "<R>"),
+                (" Describe ", "<E>"),
+                (" Delete ", "<D>"),
+                (" Problems ", "<Enter>"),
+            ] {
+                instructions.push_span(label);
+                instructions.push_span(Span::styled(key, key_style));
+            }
+            instructions.push_span(" ESC ");
+        }
+        instructions.push_span(Span::styled("<ESC>", key_style));
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(Line::from(" 🗂️ Manage Decks ").centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(inner_area);
+
+        let title = match &self.editing {
+            Some(deck) => format!("Problems in {}", deck.name),
+            None if self.decks.is_empty() => {
+                "No decks yet, press N to start one such as Blind 75".to_string()
+            }
+            None => "Decks to study from, picked with ←/→ on the menu".to_string(),
+        };
+        let mut header_lines = vec![Line::from(Span::styled(
+            title,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        if let Some((prompt, input)) = &self.prompt {
+            let label = match prompt {
+                DeckPrompt::Create => "New deck: ",
+                DeckPrompt::Rename => "New name: ",
+                DeckPrompt::Describe => "Description: ",
+            };
+            header_lines.push(Line::from(vec![
+                label.into(),
+                Span::styled(
+                    format!("{input}█"),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                " (Enter to apply, Esc to cancel)".into(),
+            ]));
+        } else if self.confirm_delete
+            && let Some(deck) = self.selected()
+        {
+            header_lines.push(Line::from(Span::styled(
+                format!(
+                    "Press D again to delete {}; its problems are kept",
+                    deck.name
+                ),
+                Style::default().fg(Color::Red),
+            )));
+        } else if let Some(message) = &self.message {
+            header_lines.push(Line::from(message.as_str()));
+        }
+        Paragraph::new(Text::from(header_lines))
+            .alignment(Alignment::Center)
+            .render(chunks[0], buf);
+
+        let selection_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+
+        let table = if self.editing.is_some() {
+            let widths = [Constraint::Length(6), Constraint::Min(10)];
+            Table::new(self.items.clone(), widths)
+                .header(Row::new(vec!["In", "Problem"]).bottom_margin(1))
+        } else {
+            let widths = [
+                Constraint::Percentage(30),
+                Constraint::Percentage(15),
+                Constraint::Percentage(55),
+            ];
+            Table::new(self.items.clone(), widths)
+                .header(Row::new(vec!["Deck", "Problems", "Description"]).bottom_margin(1))
+        };
+        let table = table
+            .column_spacing(2)
+            .row_highlight_style(selection_style)
+            .highlight_symbol(">> ");
+
+        let mut temp_state = self.list_state.clone();
+        ratatui::widgets::StatefulWidget::render(table, chunks[1], buf, &mut temp_state);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::db;
+use crate::lib::{Action, DueQueueScreen, ScreenAction, View};
+use crate::scheduler::limits;
+use crate::screens::deck_screen::scoped_title;
+use chrono::Utc;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Constraint, Direction, Layout};
+use ratatui::style::Modifier;
+use ratatui::style::{Color, Style};
+use ratatui::symbols::border;
+use ratatui::text::Text;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Table, TableState};
+use ratatui::widgets::{Borders, Paragraph};
+use ratatui::widgets::{Row, Widget};
+use std::sync::Arc;
+
+impl<'a> DueQueueScreen<'a> {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
+        let mut list_state = TableState::default();
+        list_state.select(Some(0));
+        let (items, held_back) = query_items(&db).unwrap_or_default();
+        let deck = db::current_deck(&db).ok().flatten();
+
+        Self {
+            db,
+            deck,
+            items,
+            list_state,
+            held_back,
+        }
+    }
+}
+
+fn query_items(db: &Arc<rusqlite::Connection>) -> rusqlite::Result<(Vec<Row<'static>>, usize)> {
+    let today = Utc::now().date_naive();
+    let queue = limits::daily_queue(db, today)?;
+    let review_counts = db::review_counts(db)?;
+    let mut items: Vec<Row> = vec![];
+    for (problem, days_overdue) in queue.problems {
+        let overdue_style = match days_overdue {
+            0 => Style::default().fg(Color::LightGreen),
+            1..=3 => Style::default().fg(Color::Yellow),
+            _ => Style::default().fg(Color::LightRed),
+        };
+        let rating = db::rating_label(&review_counts, &problem);
+        let cells = vec![
+            Cell::from(problem.name),
+            Cell::from(days_overdue.to_string()).style(overdue_style),
+            Cell::from(rating),
+            Cell::from(problem.entry_date.format("%Y-%m-%d %H:%M").to_string()),
+        ];
+        let row = Row::new(cells).height(2);
+        items.push(row);
+    }
+
+    Ok((items, queue.held_back))
+}
+
+impl<'a> View for DueQueueScreen<'a> {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        match key_event.code {
+            KeyCode::Char('q') => Action::Quit, // Global
+            KeyCode::Esc => Action::ShouldSwitch,
+
+            KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
+            KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
+            KeyCode::Enter => Action::ScreenSpecific(ScreenAction::MenuSelect),
+            _ => Action::NoOp,
+        }
+    }
+}
+
+impl<'a> Widget for &DueQueueScreen<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let instructions = Line::from(vec![
+            " Quit ".into(),
+            Span::styled(
+                "<Q>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Scroll ".into(),
+            Span::styled(
+                "<↑/↓>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Start Session ".into(),
+            Span::styled(
+                "<Enter>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " ESC ".into(),
+            Span::styled(
+                "<ESC>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(scoped_title(" 📅 Due Today ", self.deck.as_ref()).centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(inner_area);
+
+        let mut header = if self.items.is_empty() {
+            "Nothing due today, nice work!".to_string()
+        } else {
+            format!("{} problem(s) due, most overdue first", self.items.len())
+        };
+        if self.held_back > 0 {
+            header.push_str(&format!(
+                " ({} more held back by daily limits)",
+                self.held_back
+            ));
+        }
+        let welcome_text = Text::from(vec![Line::from(Span::styled(
+            header,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))])
+        .centered();
+
+        Paragraph::new(welcome_text)
+            .alignment(ratatui::layout::Alignment::Center)
+            .render(chunks[0], buf);
+
+        let selection_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+
+        let widths = [
+            Constraint::Percentage(40),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(30),
+        ];
+        let rows = self.items.clone();
+
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec![
+                    "Problem Name",
+                    "Days Overdue",
+                    "Last Rating",
+                    "Last Entry",
+                ])
+                .bottom_margin(1),
+            )
+            .column_spacing(5)
+            .row_highlight_style(selection_style)
+            .highlight_symbol(">> ");
+
+        let mut temp_state = self.list_state.clone();
+
+        ratatui::widgets::StatefulWidget::render(table, chunks[1], buf, &mut temp_state);
+    }
+}
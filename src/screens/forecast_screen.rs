@@ -0,0 +1,224 @@
+use crate::db;
+use crate::lib::{Action, ForecastScreen, View};
+use crate::scheduler::limits::{self, DailyLimits};
+use crate::screens::deck_screen::scoped_title;
+use chrono::Utc;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::{self, border};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph, Widget};
+use std::sync::Arc;
+
+const SHORT_RANGE: usize = 30;
+const LONG_RANGE: usize = 90;
+
+impl ForecastScreen {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
+        let days = SHORT_RANGE;
+        let (reviews, new_problems) = query_items(&db, days).unwrap_or_default();
+        let deck = db::current_deck(&db).ok().flatten();
+        Self {
+            db,
+            deck,
+            days,
+            reviews,
+            new_problems,
+        }
+    }
+}
+
+/// Counts, per day starting today, how many problems of the chosen deck come
+/// due. Anything overdue lands on today, and whatever the daily limits hold
+/// back, counting what was already rated today, moves to later days.
+fn query_items(
+    db: &Arc<rusqlite::Connection>,
+    days: usize,
+) -> rusqlite::Result<(Vec<u64>, Vec<u64>)> {
+    let today = Utc::now().date_naive();
+    let review_counts = db::review_counts(db)?;
+    let added_unrated = db::added_unrated(db)?;
+    let scope = db::deck_scope(db)?;
+
+    let mut reviews = vec![0; days];
+    let mut new_problems = vec![0; days];
+    for problem in db::active_problems(db)? {
+        if !db::in_scope(&scope, problem.id) {
+            continue;
+        }
+        let Some(due_date) = db::due_date_of(&problem) else {
+            continue;
+        };
+        let day = (due_date - today).num_days().max(0) as usize;
+        if day >= days {
+            continue;
+        }
+        if limits::is_new(&review_counts, &added_unrated, problem.id) {
+            new_problems[day] += 1;
+        } else {
+            reviews[day] += 1;
+        }
+    }
+    let done = limits::done_today(&db::load_reviews(db)?, &added_unrated, today);
+    DailyLimits::load(db)?.spill_forecast(&mut reviews, &mut new_problems, done);
+    Ok((reviews, new_problems))
+}
+
+impl View for ForecastScreen {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        match key_event.code {
+            KeyCode::Char('q') => Action::Quit, // Global
+            KeyCode::Esc => Action::ShouldSwitch,
+            KeyCode::Tab => {
+                self.days = if self.days == SHORT_RANGE {
+                    LONG_RANGE
+                } else {
+                    SHORT_RANGE
+                };
+                if let Ok((reviews, new_problems)) = query_items(&self.db, self.days) {
+                    self.reviews = reviews;
+                    self.new_problems = new_problems;
+                }
+                Action::NoOp
+            }
+            _ => Action::NoOp,
+        }
+    }
+}
+
+impl Widget for &ForecastScreen {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let instructions = Line::from(vec![
+            " Quit ".into(),
+            Span::styled(
+                "<Q>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            if self.days == SHORT_RANGE {
+                " 90 Days ".into()
+            } else {
+                " 30 Days ".into()
+            },
+            Span::styled(
+                "<Tab>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " ESC ".into(),
+            Span::styled(
+                "<ESC>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(
+                scoped_title(
+                    &format!(" 🔮 Review Forecast: next {} days ", self.days),
+                    self.deck.as_ref(),
+                )
+                .centered(),
+            )
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(inner_area);
+
+        let week_reviews: u64 = self.reviews.iter().take(7).sum();
+        let week_new: u64 = self.new_problems.iter().take(7).sum();
+        let total: u64 = self.reviews.iter().chain(&self.new_problems).sum();
+        Paragraph::new(vec![
+            Line::from(format!(
+                "Next 7 days: {week_reviews} reviews, {week_new} new  |  {total} due in the next {} days",
+                self.days
+            )),
+            Line::from(vec![
+                Span::styled("■ reviews ", Style::default().fg(Color::Rgb(45, 230, 67))),
+                Span::styled("■ new", Style::default().fg(Color::Yellow)),
+            ]),
+        ])
+        .centered()
+        .render(chunks[0], buf);
+
+        // Bars are drawn as the day's total with the new problems on top, so the
+        // visible green part is the reviews.
+        let totals: Vec<(f64, f64)> = self
+            .reviews
+            .iter()
+            .zip(&self.new_problems)
+            .enumerate()
+            .map(|(day, (reviews, new))| (day as f64, (reviews + new) as f64))
+            .filter(|(_, count)| *count > 0.0)
+            .collect();
+        let new_only: Vec<(f64, f64)> = self
+            .new_problems
+            .iter()
+            .enumerate()
+            .map(|(day, new)| (day as f64, *new as f64))
+            .filter(|(_, count)| *count > 0.0)
+            .collect();
+        let max = totals.iter().map(|(_, y)| *y).fold(1.0, f64::max);
+
+        let datasets = vec![
+            Dataset::default()
+                .name("reviews")
+                .marker(symbols::Marker::HalfBlock)
+                .graph_type(GraphType::Bar)
+                .style(Style::default().fg(Color::Rgb(45, 230, 67)))
+                .data(&totals),
+            Dataset::default()
+                .name("new")
+                .marker(symbols::Marker::HalfBlock)
+                .graph_type(GraphType::Bar)
+                .style(Style::default().fg(Color::Yellow))
+                .data(&new_only),
+        ];
+
+        let last_day = self.days.saturating_sub(1) as f64;
+        let chart = Chart::new(datasets)
+            .x_axis(
+                Axis::default()
+                    .title("days from today")
+                    .style(Style::default().fg(Color::DarkGray))
+                    .bounds([0.0, last_day])
+                    .labels([
+                        "today".to_string(),
+                        format!("+{}", self.days / 2),
+                        format!("+{}", self.days - 1),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("problems")
+                    .style(Style::default().fg(Color::DarkGray))
+                    .bounds([0.0, max])
+                    .labels([
+                        "0".to_string(),
+                        format!("{:.0}", max / 2.0),
+                        format!("{max:.0}"),
+                    ]),
+            )
+            .hidden_legend_constraints((Constraint::Length(0), Constraint::Length(0)));
+        chart.render(chunks[1], buf);
+    }
+}
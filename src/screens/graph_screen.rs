@@ -1,6 +1,9 @@
-use crate::lib::{Action, GraphScreen, Problem, View};
-use chrono::{DateTime, Datelike, NaiveDate, ParseError, TimeZone, Utc, Weekday};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crate::db;
+use crate::lib::{Action, GraphScreen, Pause, View};
+use crate::scheduler::pause;
+use crate::screens::deck_screen::scoped_title;
+use chrono::{Datelike, NaiveDate, ParseError, Utc, Weekday};
+use crossterm::event::KeyCode;
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
@@ -8,24 +11,63 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::symbols::border;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Row, Table, Widget};
-use std::io;
 use std::sync::Arc;
 
 impl GraphScreen {
     pub fn new(db: Arc<rusqlite::Connection>) -> Self {
-        let unprocessed_dates = query_items(&db);
-        let dates = unprocessed_dates.unwrap();
+        let unprocessed_dates = query_items(&db, include_suspended(&db));
+        let dates = unprocessed_dates.unwrap_or_default();
         let date = Utc::now();
         let current_year = date.year() as usize;
         let offset = get_offset(current_year).unwrap() as i32;
+        let pauses = db::load_pauses(&db).unwrap_or_default();
+        let paused_days = paused_days_in_year(&pauses, current_year as i32);
+        let streak = current_streak(&dates, &pauses, date.date_naive());
+        let deck = db::current_deck(&db).ok().flatten();
         Self {
             db,
+            deck,
             dates,
+            paused_days,
+            streak,
             current_year,
             offset,
         }
     }
 }
+
+fn paused_days_in_year(pauses: &[Pause], year: i32) -> Vec<u32> {
+    pauses
+        .iter()
+        .flat_map(|pause| {
+            pause
+                .start
+                .iter_days()
+                .take_while(move |day| *day <= pause.end)
+        })
+        .filter(|day| day.year() == year)
+        .map(|day| day.ordinal())
+        .collect()
+}
+
+/// Counts back from today over days with at least one entry. Paused days are
+/// skipped, and so is today if nothing has been done yet.
+fn current_streak(dates: &[NaiveDate], pauses: &[Pause], today: NaiveDate) -> usize {
+    let mut streak = 0;
+    let mut day = today;
+    loop {
+        if dates.contains(&day) {
+            streak += 1;
+        } else if day != today && !pause::is_paused(pauses, day) {
+            break;
+        }
+        match day.pred_opt() {
+            Some(previous) => day = previous,
+            None => break,
+        }
+    }
+    streak
+}
 fn get_offset(year: usize) -> Result<u32, ParseError> {
     let day = NaiveDate::from_ymd_opt(year as i32, 1, 1).unwrap();
 
@@ -34,51 +76,44 @@ fn get_offset(year: usize) -> Result<u32, ParseError> {
     Ok(index)
 }
 
-fn query_items(db: &Arc<rusqlite::Connection>) -> rusqlite::Result<Vec<String>> {
-    let mut db_result = db.prepare("SELECT * FROM user_problems")?;
+/// Reads the `heatmap_suspended` setting: whether suspended problems still
+/// count towards the heatmap. They do unless it is switched off.
+fn include_suspended(db: &rusqlite::Connection) -> bool {
+    db::get_bool_setting(db, "heatmap_suspended")
+        .ok()
+        .flatten()
+        .unwrap_or(true)
+}
 
-    let problem_iter = db_result.query_map([], |row| {
-        Ok(Problem {
-            name: row.get(0)?,
-            rating: row.get(1)?,
-            entry_date: row.get(2)?,
-        })
+fn query_items(
+    db: &Arc<rusqlite::Connection>,
+    include_suspended: bool,
+) -> rusqlite::Result<Vec<NaiveDate>> {
+    // Every attempt counts, not just the latest one kept on the problem
+    let mut db_result = db.prepare(if include_suspended {
+        "SELECT problem_id, reviewed_at FROM reviews"
+    } else {
+        "SELECT r.problem_id, r.reviewed_at FROM reviews r
+         JOIN user_problems p ON p.id = r.problem_id WHERE p.suspended = 0"
     })?;
-    let mut items: Vec<String> = vec![];
-    for problem_result in problem_iter {
-        let problem = problem_result?;
-        let problem_entry_date = utc_string_to_year_month_day(&problem.entry_date);
-        match problem_entry_date {
-            Ok(val) => {
-                items.push(val);
-            }
-            Err(err) => {
-                println!("error on 41: {:#?}", err);
-            }
+    let scope = db::deck_scope(db)?;
+
+    let entry_iter = db_result.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+    })?;
+    let mut items: Vec<NaiveDate> = vec![];
+    for entry_result in entry_iter {
+        let (problem_id, reviewed_at) = entry_result?;
+        if !db::in_scope(&scope, problem_id) {
+            continue;
+        }
+        if let Some(reviewed_at) = db::parse_timestamp(&reviewed_at) {
+            items.push(reviewed_at.date_naive());
         }
     }
 
     Ok(items)
 }
-fn utc_string_to_year_month_day(utc_date_str: &str) -> Result<String, ParseError> {
-    let datetime = utc_date_str.parse::<DateTime<Utc>>()?;
-
-    // 2. Extract the year, month, and day components
-    let year = datetime.year();
-    let month = datetime.month(); // Month number starting from 1
-    let day = datetime.day();
-    let final_string = format!("{}/{}/{}", year, month, day);
-    Ok(final_string)
-}
-fn to_day(date: &str) -> std::result::Result<u32, ParseError> {
-    let format = "%Y/%m/%d";
-
-    let naive_date = NaiveDate::parse_from_str(date.trim(), format)?;
-
-    let ordinal_day = naive_date.ordinal();
-
-    Ok(ordinal_day)
-}
 
 impl View for GraphScreen {
     fn draw(&self, frame: &mut Frame) {
@@ -109,7 +144,8 @@ impl Widget for &GraphScreen {
             .borders(Borders::ALL)
             .border_set(border::THICK)
             .border_style(Style::default().fg(Color::Cyan))
-            .title_top(Line::from(" 📈 Usage Graph ").centered())
+            .title_top(scoped_title(" 📈 Usage Graph ", self.deck.as_ref()).centered())
+            .title_top(Line::from(format!(" 🔥 Streak: {} day(s) ", self.streak)).right_aligned())
             .title_bottom(instructions.centered());
         container_block.clone().render(area, buf);
 
@@ -135,18 +171,12 @@ impl Widget for &GraphScreen {
         // Use one constraint for the label column
         Table::new(day_labels, [Constraint::Length(5)]).render(chunks[0], buf);
 
-        let mut dates: Vec<u32> = vec![];
-        for date in &self.dates {
-            let final_date = date.to_string();
-
-            match to_day(&final_date) {
-                Ok(val) => dates.push(val),
-                Err(e) => {
-                    eprintln!("Failed to parse date/time: {:?}", e);
-                    return;
-                }
-            }
-        }
+        let dates: Vec<u32> = self
+            .dates
+            .iter()
+            .filter(|date| date.year() as usize == self.current_year)
+            .map(|date| date.ordinal())
+            .collect();
 
         let rows: Vec<Row> = (0..7)
             .map(|day_of_week| {
@@ -160,13 +190,17 @@ impl Widget for &GraphScreen {
                         let count = dates.iter().filter(|&&d| d == day_of_year as u32).count();
 
                         match count {
+                            // Paused days are neutral, neither practiced nor missed
+                            0 if self.paused_days.contains(&(day_of_year as u32)) => {
+                                Cell::from("■").style(Style::default().fg(Color::Rgb(70, 90, 140)))
+                            }
                             0 => Cell::from("■").style(Style::default().fg(Color::DarkGray)),
                             1 => Cell::from("■")
                                 .style(Style::default().fg(Color::Rgb(133, 199, 140))), // Green
                             2 => {
                                 Cell::from("■").style(Style::default().fg(Color::Rgb(45, 230, 67)))
                             }
-                            _ => Cell::from("■").style(Style::default().fg(Color::DarkGray)),
+                            _ => Cell::from("■").style(Style::default().fg(Color::Rgb(0, 255, 60))),
                         }
                     })
                     .collect();
@@ -1,5 +1,6 @@
+use crate::db;
 use crate::lib::{Action, MenuScreen, ScreenAction, View};
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -12,42 +13,52 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::Borders;
 use ratatui::widgets::{Block, List, ListState, Paragraph};
 use ratatui::widgets::{ListItem, Widget};
-use std::io;
+use std::sync::Arc;
 
-impl Default for MenuScreen {
-    fn default() -> Self {
+impl MenuScreen {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
         let mut menu_state = ListState::default();
         // Set the initial selection to the first item (0)
         menu_state.select(Some(0));
+        let decks = db::load_decks(&db).unwrap_or_default();
+        let deck = db::current_deck(&db).ok().flatten();
         Self {
+            db,
             menu_state,
             menu_options: &[
                 "1. Add / Update Problem",
                 "2. List All Problems ",
                 "3. See Graph of Problems ",
+                "4. Due Today ",
+                "5. Start Review Session ",
+                "6. Review Forecast ",
+                "7. Scheduler Simulator ",
+                "8. Manage Tags ",
+                "9. Manage Decks ",
             ],
+            decks,
+            deck,
         }
     }
-}
 
-impl View for MenuScreen {
-    fn handle_events(&mut self) -> io::Result<Action> {
-        let mut some_action = Action::NoOp;
-        match event::read()? {
-            Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                let result = self.handle_key_event(key_event);
-                match result {
-                    Action::Quit | Action::ShouldSwitch | Action::ScreenSpecific(_) => {
-                        some_action = result;
-                    }
-                    _ => {}
-                }
-            }
-            _ => {}
-        };
-        Ok(some_action)
+    /// Steps through "all problems" followed by each deck, and keeps the
+    /// choice for every other screen.
+    fn cycle_deck(&mut self, direction: isize) {
+        let position = self
+            .deck
+            .as_ref()
+            .and_then(|deck| self.decks.iter().position(|other| other.id == deck.id))
+            .map_or(0, |index| index + 1);
+        let choices = self.decks.len() as isize + 1;
+        let next = (position as isize + direction).rem_euclid(choices) as usize;
+        let deck = next.checked_sub(1).map(|index| self.decks[index].clone());
+        if db::set_current_deck(&self.db, deck.as_ref().map(|deck| deck.id)).is_ok() {
+            self.deck = deck;
+        }
     }
+}
 
+impl View for MenuScreen {
     fn handle_key_event(&mut self, key_event: KeyEvent) -> Action {
         match key_event.code {
             KeyCode::Char('q') => Action::Quit, // Global
@@ -57,6 +68,14 @@ impl View for MenuScreen {
             KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
             KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
             KeyCode::Enter => Action::ScreenSpecific(ScreenAction::MenuSelect),
+            KeyCode::Left => {
+                self.cycle_deck(-1);
+                Action::NoOp
+            }
+            KeyCode::Right => {
+                self.cycle_deck(1);
+                Action::NoOp
+            }
             _ => Action::NoOp,
         }
     }
@@ -84,6 +103,13 @@ impl Widget for &MenuScreen {
                     .fg(Color::Blue)
                     .add_modifier(Modifier::BOLD),
             ),
+            " Deck ".into(),
+            Span::styled(
+                "<←/→>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
             " Enter".into(),
             Span::styled(
                 "<Enter>",
@@ -114,7 +140,7 @@ impl Widget for &MenuScreen {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(3),
-                Constraint::Length(5),
+                Constraint::Length(self.menu_options.len() as u16 + 2),
                 Constraint::Min(0),
             ])
             .split(inner_area);
@@ -122,12 +148,36 @@ impl Widget for &MenuScreen {
         let welcome_area = chunks[0];
         let menu_area = chunks[1];
 
-        let welcome_text = Text::from(vec![Line::from(Span::styled(
-            "Welcome to SRL-Rust Problem Tracker!",
-            Style::default()
-                .fg(Color::Yellow)
-                .add_modifier(Modifier::BOLD),
-        ))])
+        let deck_line = match &self.deck {
+            Some(deck) => {
+                let mut spans = vec![
+                    "Deck: ".into(),
+                    Span::styled(
+                        format!("◀ {} ▶", deck.name),
+                        Style::default().fg(Color::Green),
+                    ),
+                    format!(" ({} problems)", deck.problems).into(),
+                ];
+                if !deck.description.is_empty() {
+                    spans.push(format!(" · {}", deck.description).into());
+                }
+                Line::from(spans)
+            }
+            None if self.decks.is_empty() => Line::from("Deck: all problems"),
+            None => Line::from(vec![
+                "Deck: ".into(),
+                Span::styled("◀ All problems ▶", Style::default().fg(Color::Green)),
+            ]),
+        };
+        let welcome_text = Text::from(vec![
+            Line::from(Span::styled(
+                "Welcome to SRL-Rust Problem Tracker!",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            deck_line,
+        ])
         .centered();
 
         Paragraph::new(welcome_text)
@@ -1,5 +1,13 @@
 pub mod add_problem_screen;
+pub mod deck_screen;
+pub mod due_queue_screen;
+pub mod forecast_screen;
 pub mod graph_screen;
 pub mod home_screen;
 pub mod menu_screen;
+pub mod notes_screen;
+pub mod review_session_screen;
+pub mod simulator_screen;
+pub mod solution_screen;
+pub mod tag_screen;
 pub mod view_all_problems_screen;
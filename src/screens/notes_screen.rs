@@ -0,0 +1,194 @@
+use crate::db;
+use crate::lib::{Action, NotesScreen, ProblemFilter, View};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Paragraph, Widget};
+use std::sync::Arc;
+use tui_textarea::{CursorMove, TextArea};
+
+impl<'a> NotesScreen<'a> {
+    pub fn new(db: Arc<rusqlite::Connection>, problem_id: i64, filter: ProblemFilter) -> Self {
+        let problem_name = db::load_problem(&db, problem_id)
+            .ok()
+            .flatten()
+            .map(|problem| problem.name)
+            .unwrap_or_default();
+        let saved = db::load_notes(&db, problem_id).unwrap_or_default();
+
+        let mut editor = TextArea::from(saved.lines());
+        editor.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Notes (markdown) "),
+        );
+        editor.set_cursor_line_style(Style::default().add_modifier(Modifier::UNDERLINED));
+        editor.set_line_number_style(Style::default().fg(Color::DarkGray));
+        editor.move_cursor(CursorMove::Bottom);
+        editor.move_cursor(CursorMove::End);
+
+        Self {
+            db,
+            problem_id,
+            problem_name,
+            editor,
+            saved,
+            filter,
+            confirm_discard: false,
+            message: None,
+        }
+    }
+
+    fn text(&self) -> String {
+        self.editor.lines().join("\n").trim_end().to_string()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.text() != self.saved
+    }
+
+    fn save(&mut self) {
+        let text = self.text();
+        self.message = match db::set_notes(&self.db, self.problem_id, &text) {
+            Ok(()) => {
+                self.saved = text;
+                Some("Notes saved".to_string())
+            }
+            Err(error) => Some(format!("There was an error saving the notes: {error}")),
+        };
+    }
+}
+
+/// Renders notes with just enough markdown to scan them: headings stand out,
+/// list items get bullets and fenced code is set apart.
+pub fn markdown_lines(notes: &str) -> Vec<Line<'static>> {
+    let mut in_code = false;
+    let mut lines = vec![];
+    for line in notes.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        let styled = if in_code {
+            Line::styled(format!("  {line}"), Style::default().fg(Color::Gray))
+        } else if trimmed.starts_with('#') {
+            Line::styled(
+                trimmed.trim_start_matches('#').trim().to_string(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            let indent = &line[..line.len() - trimmed.len()];
+            Line::from(format!("{indent}• {item}"))
+        } else {
+            Line::from(line.to_string())
+        };
+        lines.push(styled);
+    }
+    lines
+}
+
+impl<'a> View for NotesScreen<'a> {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> Action {
+        if key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && key_event.code == KeyCode::Char('s')
+        {
+            self.confirm_discard = false;
+            self.save();
+            return Action::NoOp;
+        }
+
+        match key_event.code {
+            KeyCode::Esc if self.confirm_discard || !self.is_dirty() => Action::ShouldSwitch,
+            KeyCode::Esc => {
+                self.confirm_discard = true;
+                Action::NoOp
+            }
+            _ => {
+                self.confirm_discard = false;
+                self.message = None;
+                self.editor.input(key_event);
+                Action::NoOp
+            }
+        }
+    }
+}
+
+impl<'a> Widget for &NotesScreen<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let instructions = Line::from(vec![
+            " Save ".into(),
+            Span::styled(
+                "<Ctrl+S>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Scroll ".into(),
+            Span::styled(
+                "<PgUp/PgDn>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Back ".into(),
+            Span::styled(
+                "<ESC>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(Line::from(" 📝 Problem Notes ").centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(inner_area);
+
+        let mut header_lines = vec![Line::from(Span::styled(
+            self.problem_name.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        if self.confirm_discard {
+            header_lines.push(Line::from(Span::styled(
+                "Unsaved changes: Esc again to discard them, Ctrl+S to save",
+                Style::default().fg(Color::Red),
+            )));
+        } else if let Some(message) = &self.message {
+            header_lines.push(Line::from(message.as_str()));
+        } else if self.is_dirty() {
+            header_lines.push(Line::from("Unsaved changes"));
+        }
+
+        Paragraph::new(Text::from(header_lines))
+            .alignment(Alignment::Center)
+            .render(chunks[0], buf);
+
+        self.editor.render(chunks[1], buf);
+    }
+}
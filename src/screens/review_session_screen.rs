@@ -0,0 +1,389 @@
+use crate::db;
+use crate::lib::{
+    Action, Rating, Relation, RelationKind, ReviewSessionScreen, SessionResult, Stopwatch, View,
+};
+use crate::scheduler::limits;
+use crate::screens::deck_screen::scoped_title;
+use crate::stopwatch::format_duration;
+use chrono::Utc;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::{Constraint, Direction, Layout};
+use ratatui::style::Modifier;
+use ratatui::style::{Color, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, Widget};
+use std::sync::Arc;
+
+impl ReviewSessionScreen {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
+        let today = Utc::now().date_naive();
+        let queue = limits::daily_queue(&db, today).unwrap_or_default();
+        let deck = db::current_deck(&db).ok().flatten();
+        let review_counts = db::review_counts(&db).unwrap_or_default();
+
+        Self {
+            db,
+            deck,
+            queue: queue.problems,
+            review_counts,
+            current: 0,
+            results: vec![],
+            skipped: 0,
+            held_back: queue.held_back,
+            failed_to_update: false,
+            stopwatch: Stopwatch::default(),
+            suggestion: None,
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current >= self.queue.len()
+    }
+
+    /// Records the rating for the current problem, the same way the add /
+    /// update screen does, and moves on to the next one. A running stopwatch
+    /// stops here and its time goes with the rating.
+    fn rate_current(&mut self, rating: Rating) {
+        let Some((problem, _)) = self.queue.get(self.current) else {
+            return;
+        };
+        let (id, name) = (problem.id, problem.name.clone());
+        let duration_secs = self.stopwatch.seconds();
+        match db::update_problem(&self.db, id, rating, &Utc::now(), duration_secs) {
+            Ok(true) => {
+                let updated = db::load_problem(&self.db, id).ok().flatten();
+                self.results.push(SessionResult {
+                    problem_name: name.clone(),
+                    rating,
+                    duration_secs,
+                    interval: updated.as_ref().map_or(0, |p| p.state.interval),
+                    mastered: updated.as_ref().is_some_and(|p| p.mastered),
+                });
+                self.failed_to_update = false;
+                self.stopwatch.reset();
+                self.current += 1;
+                self.suggestion = if rating < Rating::Difficult {
+                    self.step_back(id).map(|relation| (name, relation))
+                } else {
+                    None
+                };
+            }
+            Ok(false) | Err(_) => self.failed_to_update = true,
+        }
+    }
+
+    /// The linked problem best practised after a lapse on `problem_id`, if
+    /// any: one it builds on, one it follows up, or a similar one. Problems
+    /// already seen this session, or up next, are left out.
+    fn step_back(&self, problem_id: i64) -> Option<Relation> {
+        let seen: Vec<i64> = self.queue[..(self.current + 1).min(self.queue.len())]
+            .iter()
+            .map(|(problem, _)| problem.id)
+            .collect();
+        db::problem_relations(&self.db)
+            .ok()?
+            .remove(&problem_id)?
+            .into_iter()
+            .filter(|relation| {
+                relation.step_back_rank().is_some() && !seen.contains(&relation.problem_id)
+            })
+            .min_by_key(|relation| relation.step_back_rank())
+    }
+
+    /// Puts the suggested problem next in the queue, moving it up if it was
+    /// already due later in the session.
+    fn jump_to_suggestion(&mut self) {
+        let Some((_, relation)) = self.suggestion.take() else {
+            return;
+        };
+        let Some(problem) = db::load_problem(&self.db, relation.problem_id)
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        if let Some(later) = self.queue[self.current..]
+            .iter()
+            .position(|(queued, _)| queued.id == problem.id)
+        {
+            let entry = self.queue.remove(self.current + later);
+            self.queue.insert(self.current, entry);
+            return;
+        }
+        let today = Utc::now().date_naive();
+        let days_overdue =
+            db::due_date_of(&problem).map_or(0, |due| (today - due).num_days().max(0));
+        self.queue.insert(self.current, (problem, days_overdue));
+    }
+}
+
+impl View for ReviewSessionScreen {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: KeyEvent) -> Action {
+        match key_event.code {
+            KeyCode::Esc => Action::ShouldSwitch,
+            KeyCode::Enter if self.is_finished() => Action::ShouldSwitch,
+            KeyCode::Char(c @ '1'..='5') if !self.is_finished() => {
+                if let Some(rating) = Rating::from_value(c as u8 - b'0') {
+                    self.rate_current(rating);
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('t') if !self.is_finished() => {
+                self.stopwatch.toggle();
+                Action::NoOp
+            }
+            KeyCode::Char('j') if self.suggestion.is_some() => {
+                self.stopwatch.reset();
+                self.jump_to_suggestion();
+                Action::NoOp
+            }
+            KeyCode::Char('s') if !self.is_finished() => {
+                self.suggestion = None;
+                self.stopwatch.reset();
+                self.skipped += 1;
+                self.current += 1;
+                Action::NoOp
+            }
+            _ => Action::NoOp,
+        }
+    }
+}
+
+fn key_hint(label: &'static str, key: &'static str) -> [Span<'static>; 2] {
+    [
+        label.into(),
+        Span::styled(
+            key,
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ),
+    ]
+}
+
+/// A line offering the suggested problem, after a lapse on another one.
+fn suggestion_line(session: &ReviewSessionScreen) -> Line<'static> {
+    let Some((rated, relation)) = &session.suggestion else {
+        return Line::default();
+    };
+    let link = match (relation.kind, relation.incoming) {
+        (RelationKind::Prerequisite, true) => "builds on",
+        (RelationKind::FollowUp, false) => "is a follow-up of",
+        _ => "is similar to",
+    };
+    Line::from(vec![
+        Span::styled(
+            format!("Stuck on {rated}? It {link} {}, ", relation.problem_name),
+            Style::default().fg(Color::LightMagenta),
+        ),
+        Span::styled(
+            "<J>",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            " to practise that next",
+            Style::default().fg(Color::LightMagenta),
+        ),
+    ])
+}
+
+impl Widget for &ReviewSessionScreen {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut hints = vec![];
+        if self.is_finished() {
+            hints.extend(key_hint(" Done ", "<Enter>"));
+        } else {
+            hints.extend(key_hint(" Rate ", "<1-5>"));
+            hints.extend(key_hint(
+                if self.stopwatch.is_running() {
+                    " Pause timer "
+                } else {
+                    " Start timer "
+                },
+                "<T>",
+            ));
+            hints.extend(key_hint(" Skip ", "<S>"));
+        }
+        if self.suggestion.is_some() {
+            hints.extend(key_hint(" Practise related ", "<J>"));
+        }
+        hints.extend(key_hint(" ESC ", "<ESC>"));
+        let instructions = Line::from(hints);
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(scoped_title(" 🧠 Review Session ", self.deck.as_ref()).centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        if self.queue.is_empty() {
+            Paragraph::new("Nothing is due right now, come back later!")
+                .centered()
+                .style(Style::default().fg(Color::LightGreen))
+                .render(inner_area, buf);
+        } else if self.is_finished() {
+            render_summary(self, inner_area, buf);
+        } else {
+            render_current(self, inner_area, buf);
+        }
+    }
+}
+
+fn render_current(session: &ReviewSessionScreen, area: Rect, buf: &mut Buffer) {
+    let (problem, days_overdue) = &session.queue[session.current];
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(3),
+            Constraint::Length(5),
+            Constraint::Min(0),
+        ])
+        .split(area);
+
+    Paragraph::new(format!(
+        "Problem {} of {}",
+        session.current + 1,
+        session.queue.len()
+    ))
+    .centered()
+    .style(Style::default().fg(Color::DarkGray))
+    .render(chunks[0], buf);
+
+    Paragraph::new(problem.name.as_str())
+        .centered()
+        .style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )
+        .block(Block::default().borders(Borders::ALL))
+        .render(chunks[1], buf);
+
+    let timer = if session.stopwatch.is_running() {
+        Style::default()
+            .fg(Color::LightGreen)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    let details = Text::from(vec![
+        Line::from(format!(
+            "Last rating: {}",
+            db::rating_label(&session.review_counts, problem)
+        )),
+        Line::from(format!(
+            "Last entry: {}",
+            problem.entry_date.format("%Y-%m-%d %H:%M")
+        )),
+        Line::from(format!("Days overdue: {days_overdue}")),
+        Line::from(Span::styled(
+            format!(
+                "⏱ {}",
+                format_duration(session.stopwatch.elapsed().as_secs() as i64)
+            ),
+            timer,
+        )),
+    ])
+    .centered();
+    Paragraph::new(details).render(chunks[2], buf);
+
+    let notice = if session.failed_to_update {
+        Line::styled(
+            "Could not save that rating, try again",
+            Style::default().fg(Color::LightRed),
+        )
+    } else {
+        suggestion_line(session)
+    };
+    Paragraph::new(notice).centered().render(chunks[3], buf);
+}
+
+fn render_summary(session: &ReviewSessionScreen, area: Rect, buf: &mut Buffer) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(7), Constraint::Min(0)])
+        .split(area);
+
+    let reviewed = session.results.len();
+    let average = if reviewed == 0 {
+        0.0
+    } else {
+        session
+            .results
+            .iter()
+            .map(|r| r.rating.value() as f64)
+            .sum::<f64>()
+            / reviewed as f64
+    };
+    let mastered = session.results.iter().filter(|r| r.mastered).count();
+
+    let summary = Text::from(vec![
+        Line::from(Span::styled(
+            "Session complete!",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!(
+            "Reviewed {reviewed}, skipped {}, average rating {average:.1}",
+            session.skipped
+        )),
+        Line::from(format!("Newly mastered: {mastered}")),
+        Line::from(if session.held_back > 0 {
+            format!(
+                "{} more due problem(s) held back by daily limits",
+                session.held_back
+            )
+        } else {
+            String::new()
+        }),
+        suggestion_line(session),
+    ])
+    .centered();
+    Paragraph::new(summary).render(chunks[0], buf);
+
+    let rows: Vec<Row> = session
+        .results
+        .iter()
+        .map(|result| {
+            let next = if result.mastered {
+                "mastered".to_string()
+            } else {
+                format!("in {} day(s)", result.interval)
+            };
+            Row::new(vec![
+                Cell::from(result.problem_name.clone()),
+                Cell::from(result.rating.to_string()),
+                Cell::from(result.duration_secs.map_or(String::new(), format_duration)),
+                Cell::from(next),
+            ])
+        })
+        .collect();
+
+    let widths = [
+        Constraint::Percentage(40),
+        Constraint::Percentage(15),
+        Constraint::Percentage(15),
+        Constraint::Percentage(30),
+    ];
+    Table::new(rows, widths)
+        .header(Row::new(vec!["Problem Name", "Rating", "Time", "Next Review"]).bottom_margin(1))
+        .column_spacing(5)
+        .render(chunks[1], buf);
+}
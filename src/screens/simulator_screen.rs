@@ -0,0 +1,211 @@
+use crate::lib::{Action, ScreenAction, SimulationResult, SimulatorScreen, View};
+use crate::scheduler::simulate;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::{self, border};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{
+    Axis, Block, Borders, Cell, Chart, Dataset, GraphType, Row, Table, TableState, Widget,
+};
+use std::sync::Arc;
+
+const SHORT_RANGE: usize = 30;
+const LONG_RANGE: usize = 90;
+
+impl<'a> SimulatorScreen<'a> {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
+        let mut list_state = TableState::default();
+        list_state.select(Some(0));
+        let days = SHORT_RANGE;
+        let results = simulate::run(&db, days).unwrap_or_default();
+        let items = query_items(&results);
+        Self {
+            db,
+            results,
+            items,
+            list_state,
+            days,
+        }
+    }
+}
+
+fn query_items(results: &[SimulationResult]) -> Vec<Row<'static>> {
+    results
+        .iter()
+        .map(|result| {
+            let total: u64 = result.daily_reviews.iter().sum();
+            let peak = result.daily_reviews.iter().max().copied().unwrap_or(0);
+            Row::new(vec![
+                Cell::from(result.name.clone()),
+                Cell::from(format!("{:.1}", result.steady_per_day)),
+                Cell::from(total.to_string()),
+                Cell::from(peak.to_string()),
+                Cell::from(format!("{:.1}%", result.expected_retention * 100.0)),
+            ])
+        })
+        .collect()
+}
+
+impl<'a> View for SimulatorScreen<'a> {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        match key_event.code {
+            KeyCode::Char('q') => Action::Quit, // Global
+            KeyCode::Esc => Action::ShouldSwitch,
+            KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
+            KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
+            KeyCode::Tab => {
+                self.days = if self.days == SHORT_RANGE {
+                    LONG_RANGE
+                } else {
+                    SHORT_RANGE
+                };
+                if let Ok(results) = simulate::run(&self.db, self.days) {
+                    self.items = query_items(&results);
+                    self.results = results;
+                }
+                Action::NoOp
+            }
+            _ => Action::NoOp,
+        }
+    }
+}
+
+impl<'a> Widget for &SimulatorScreen<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let instructions = Line::from(vec![
+            " Quit ".into(),
+            Span::styled(
+                "<Q>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Scroll ".into(),
+            Span::styled(
+                "<↑/↓>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            if self.days == SHORT_RANGE {
+                " 90 Days ".into()
+            } else {
+                " 30 Days ".into()
+            },
+            Span::styled(
+                "<Tab>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " ESC ".into(),
+            Span::styled(
+                "<ESC>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(Line::from(" 🧪 Scheduler Simulator ").centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(self.items.len() as u16 + 3),
+                Constraint::Min(0),
+            ])
+            .split(inner_area);
+
+        let selection_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+
+        let widths = [
+            Constraint::Percentage(40),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+            Constraint::Percentage(15),
+        ];
+        let table = Table::new(self.items.clone(), widths)
+            .header(
+                Row::new(vec![
+                    "Scheduler".to_string(),
+                    "Long run / day".to_string(),
+                    format!("Next {} days", self.days),
+                    "Peak day".to_string(),
+                    "Retention".to_string(),
+                ])
+                .bottom_margin(1),
+            )
+            .column_spacing(2)
+            .row_highlight_style(selection_style)
+            .highlight_symbol(">> ");
+
+        let mut temp_state = self.list_state.clone();
+        ratatui::widgets::StatefulWidget::render(table, chunks[0], buf, &mut temp_state);
+
+        let Some(selected) = self.list_state.selected().and_then(|i| self.results.get(i)) else {
+            return;
+        };
+
+        let points: Vec<(f64, f64)> = selected
+            .daily_reviews
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(day, count)| (day as f64, *count as f64))
+            .collect();
+        let max = points.iter().map(|(_, y)| *y).fold(1.0, f64::max);
+
+        let datasets = vec![
+            Dataset::default()
+                .marker(symbols::Marker::HalfBlock)
+                .graph_type(GraphType::Bar)
+                .style(Style::default().fg(Color::Rgb(45, 230, 67)))
+                .data(&points),
+        ];
+        let chart = Chart::new(datasets)
+            .block(
+                Block::default()
+                    .borders(Borders::TOP)
+                    .title(format!(" Daily reviews: {} ", selected.name)),
+            )
+            .x_axis(
+                Axis::default()
+                    .title("days from today")
+                    .style(Style::default().fg(Color::DarkGray))
+                    .bounds([0.0, self.days.saturating_sub(1) as f64])
+                    .labels([
+                        "today".to_string(),
+                        format!("+{}", self.days / 2),
+                        format!("+{}", self.days - 1),
+                    ]),
+            )
+            .y_axis(
+                Axis::default()
+                    .title("reviews")
+                    .style(Style::default().fg(Color::DarkGray))
+                    .bounds([0.0, max])
+                    .labels(["0".to_string(), format!("{max:.0}")]),
+            );
+        chart.render(chunks[1], buf);
+    }
+}
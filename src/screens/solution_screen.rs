@@ -0,0 +1,221 @@
+use crate::db;
+use crate::lib::{Action, ProblemFilter, SolutionScreen, View};
+use crate::stopwatch::format_duration;
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Paragraph, Widget};
+use std::sync::Arc;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{FontStyle, ThemeSet};
+use syntect::parsing::SyntaxSet;
+use syntect::util::LinesWithEndings;
+
+const THEME: &str = "base16-ocean.dark";
+
+impl SolutionScreen {
+    pub fn new(db: Arc<rusqlite::Connection>, problem_id: i64, filter: ProblemFilter) -> Self {
+        let problem_name = db::load_problem(&db, problem_id)
+            .ok()
+            .flatten()
+            .map(|problem| problem.name)
+            .unwrap_or_default();
+        let attempts = db::load_attempts(&db, problem_id).unwrap_or_default();
+
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        let themes = ThemeSet::load_defaults();
+        let highlighted = attempts
+            .iter()
+            .map(|attempt| {
+                highlight(
+                    &syntaxes,
+                    &themes,
+                    &attempt.code,
+                    attempt.language.as_deref(),
+                )
+            })
+            .collect();
+
+        Self {
+            problem_id,
+            problem_name,
+            current: attempts.len().saturating_sub(1),
+            attempts,
+            highlighted,
+            scroll: 0,
+            filter,
+        }
+    }
+
+    fn page(&mut self, direction: isize) {
+        let last = self.attempts.len().saturating_sub(1);
+        self.current = self.current.saturating_add_signed(direction).min(last);
+        self.scroll = 0;
+    }
+}
+
+/// Colours `code` with the syntax for `language`, matched by name or file
+/// extension; unknown languages are shown as plain text.
+fn highlight(
+    syntaxes: &SyntaxSet,
+    themes: &ThemeSet,
+    code: &str,
+    language: Option<&str>,
+) -> Vec<Line<'static>> {
+    let syntax = language
+        .and_then(|language| syntaxes.find_syntax_by_token(language))
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, &themes.themes[THEME]);
+
+    LinesWithEndings::from(code)
+        .map(|line| match highlighter.highlight_line(line, syntaxes) {
+            Ok(regions) => Line::from(
+                regions
+                    .into_iter()
+                    .map(|(style, text)| {
+                        let mut span_style = Style::default().fg(Color::Rgb(
+                            style.foreground.r,
+                            style.foreground.g,
+                            style.foreground.b,
+                        ));
+                        if style.font_style.contains(FontStyle::BOLD) {
+                            span_style = span_style.add_modifier(Modifier::BOLD);
+                        }
+                        if style.font_style.contains(FontStyle::ITALIC) {
+                            span_style = span_style.add_modifier(Modifier::ITALIC);
+                        }
+                        Span::styled(text.trim_end_matches('\n').to_string(), span_style)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Err(_) => Line::from(line.trim_end_matches('\n').to_string()),
+        })
+        .collect()
+}
+
+impl View for SolutionScreen {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        match key_event.code {
+            KeyCode::Char('q') => Action::Quit, // Global
+            KeyCode::Esc => Action::ShouldSwitch,
+
+            KeyCode::Left => {
+                self.page(-1);
+                Action::NoOp
+            }
+            KeyCode::Right => {
+                self.page(1);
+                Action::NoOp
+            }
+            KeyCode::Down => {
+                let lines = self.highlighted.get(self.current).map_or(0, Vec::len);
+                if usize::from(self.scroll) + 1 < lines {
+                    self.scroll += 1;
+                }
+                Action::NoOp
+            }
+            KeyCode::Up => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::NoOp
+            }
+            _ => Action::NoOp,
+        }
+    }
+}
+
+impl Widget for &SolutionScreen {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let instructions = Line::from(vec![
+            " Quit ".into(),
+            Span::styled(
+                "<Q>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Attempt ".into(),
+            Span::styled(
+                "<←/→>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Scroll ".into(),
+            Span::styled(
+                "<↑/↓>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Back ".into(),
+            Span::styled(
+                "<ESC>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(Line::from(" 🧩 Solution History ").centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(inner_area);
+
+        let mut header_lines = vec![Line::from(Span::styled(
+            self.problem_name.clone(),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        let Some(attempt) = self.attempts.get(self.current) else {
+            header_lines.push(Line::from(
+                "No code saved yet. Add it with a rating from the Add / Update screen",
+            ));
+            Paragraph::new(Text::from(header_lines))
+                .alignment(Alignment::Center)
+                .render(chunks[0], buf);
+            return;
+        };
+        let mut summary = format!(
+            "Attempt {} of {}, {}, rated {}",
+            self.current + 1,
+            self.attempts.len(),
+            attempt.reviewed_at.format("%Y-%m-%d"),
+            attempt.rating
+        );
+        if let Some(seconds) = attempt.duration_secs {
+            summary.push_str(&format!(", solved in {}", format_duration(seconds)));
+        }
+        header_lines.push(Line::from(summary));
+        Paragraph::new(Text::from(header_lines))
+            .alignment(Alignment::Center)
+            .render(chunks[0], buf);
+
+        let code = self.highlighted[self.current].clone();
+        Paragraph::new(code)
+            .scroll((self.scroll, 0))
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " {} ",
+                attempt.language.as_deref().unwrap_or("plain text")
+            )))
+            .render(chunks[1], buf);
+    }
+}
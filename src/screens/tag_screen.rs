@@ -0,0 +1,259 @@
+use crate::db;
+use crate::lib::{Action, ScreenAction, Tag, TagScreen, View};
+use crossterm::event::KeyCode;
+use ratatui::Frame;
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::symbols::border;
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Widget};
+use std::sync::Arc;
+
+impl<'a> TagScreen<'a> {
+    pub fn new(db: Arc<rusqlite::Connection>) -> Self {
+        let mut list_state = TableState::default();
+        list_state.select(Some(0));
+        let tags = db::load_tags(&db).unwrap_or_default();
+        let items = query_items(&tags);
+        Self {
+            db,
+            tags,
+            items,
+            list_state,
+            rename_input: None,
+            merge_from: None,
+            message: None,
+        }
+    }
+
+    fn selected(&self) -> Option<&Tag> {
+        self.tags.get(self.list_state.selected()?)
+    }
+
+    fn reload(&mut self) {
+        if let Ok(tags) = db::load_tags(&self.db) {
+            self.items = query_items(&tags);
+            self.tags = tags;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state
+            .select(Some(selected.min(self.items.len().saturating_sub(1))));
+    }
+
+    /// Renames the selected tag. Renaming it to another tag's name merges the
+    /// two instead.
+    fn apply_rename(&mut self, input: &str) {
+        let Some(tag) = self.selected().cloned() else {
+            return;
+        };
+        let name = db::normalize_name(input);
+        if name.is_empty() {
+            self.message = Some("A tag needs a name".to_string());
+            return;
+        }
+        self.message = match db::rename_tag(&self.db, tag.id, &name) {
+            Ok(true) => Some(format!("Renamed {} to {name}", tag.name)),
+            Ok(false) => self
+                .tags
+                .iter()
+                .find(|other| other.name.eq_ignore_ascii_case(&name))
+                .map(|into| self.merge(&tag, into)),
+            Err(error) => Some(format!("There was an error renaming {}: {error}", tag.name)),
+        };
+        self.reload();
+    }
+
+    fn merge(&self, from: &Tag, into: &Tag) -> String {
+        match db::merge_tags(&self.db, from.id, into.id) {
+            Ok(()) => format!("Merged {} into {}", from.name, into.name),
+            Err(error) => format!("There was an error merging {}: {error}", from.name),
+        }
+    }
+}
+
+fn query_items(tags: &[Tag]) -> Vec<Row<'static>> {
+    tags.iter()
+        .map(|tag| {
+            Row::new(vec![
+                Cell::from(tag.name.clone()),
+                Cell::from(tag.problems.to_string()),
+            ])
+        })
+        .collect()
+}
+
+impl<'a> View for TagScreen<'a> {
+    fn draw(&self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        if let Some(input) = self.rename_input.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let input = self.rename_input.take().unwrap_or_default();
+                    self.apply_rename(&input);
+                }
+                KeyCode::Esc => self.rename_input = None,
+                _ => {}
+            }
+            return Action::NoOp;
+        }
+
+        match key_event.code {
+            KeyCode::Char('q') => Action::Quit, // Global
+            KeyCode::Esc if self.merge_from.is_some() => {
+                self.merge_from = None;
+                Action::NoOp
+            }
+            KeyCode::Esc => Action::ShouldSwitch,
+            KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
+            KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
+            KeyCode::Char('r') => {
+                if let Some(tag) = self.selected() {
+                    self.rename_input = Some(tag.name.clone());
+                    self.message = None;
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('m') => {
+                self.merge_from = self.selected().cloned();
+                self.message = None;
+                Action::NoOp
+            }
+            KeyCode::Enter => {
+                match (self.merge_from.take(), self.selected().cloned()) {
+                    (Some(from), Some(into)) if from.id == into.id => self.merge_from = Some(from),
+                    (Some(from), Some(into)) => {
+                        self.message = Some(self.merge(&from, &into));
+                        self.reload();
+                    }
+                    _ => {}
+                }
+                Action::NoOp
+            }
+            _ => Action::NoOp,
+        }
+    }
+}
+
+impl<'a> Widget for &TagScreen<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut instructions = Line::from(vec![
+            " Quit ".into(),
+            Span::styled(
+                "<Q>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Scroll ".into(),
+            Span::styled(
+                "<↑/↓>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            " Rename ".into(),
+            Span::styled(
+                "<R>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]);
+        if self.merge_from.is_some() {
+            instructions.push_span(" Merge into selected ");
+            instructions.push_span(Span::styled(
+                "<Enter>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            instructions.push_span(" Cancel ");
+        } else {
+            instructions.push_span(" Merge ");
+            instructions.push_span(Span::styled(
+                "<M>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ));
+            instructions.push_span(" ESC ");
+        }
+        instructions.push_span(Span::styled(
+            "<ESC>",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ));
+
+        let container_block = Block::default()
+            .borders(Borders::ALL)
+            .border_set(border::THICK)
+            .border_style(Style::default().fg(Color::Cyan))
+            .title_top(Line::from(" 🏷️ Manage Tags ").centered())
+            .title_bottom(instructions.centered());
+        container_block.clone().render(area, buf);
+
+        let inner_area = container_block.inner(area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .split(inner_area);
+
+        let mut header_lines = vec![Line::from(Span::styled(
+            if self.tags.is_empty() {
+                "No tags yet, add some when adding or updating a problem"
+            } else {
+                "Tags used across your problems"
+            },
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        if let Some(input) = &self.rename_input {
+            header_lines.push(Line::from(vec![
+                "New name: ".into(),
+                Span::styled(
+                    format!("{input}█"),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                " (Enter to apply, Esc to cancel)".into(),
+            ]));
+        } else if let Some(from) = &self.merge_from {
+            header_lines.push(Line::from(format!(
+                "Pick the tag to merge {} into",
+                from.name
+            )));
+        } else if let Some(message) = &self.message {
+            header_lines.push(Line::from(message.as_str()));
+        }
+        Paragraph::new(Text::from(header_lines))
+            .alignment(Alignment::Center)
+            .render(chunks[0], buf);
+
+        let selection_style = Style::default()
+            .fg(Color::Black)
+            .bg(Color::Green)
+            .add_modifier(Modifier::BOLD);
+
+        let widths = [Constraint::Percentage(70), Constraint::Percentage(30)];
+        let table = Table::new(self.items.clone(), widths)
+            .header(Row::new(vec!["Tag", "Problems"]).bottom_margin(1))
+            .column_spacing(2)
+            .row_highlight_style(selection_style)
+            .highlight_symbol(">> ");
+
+        let mut temp_state = self.list_state.clone();
+        ratatui::widgets::StatefulWidget::render(table, chunks[1], buf, &mut temp_state);
+    }
+}
@@ -1,5 +1,14 @@
-use crate::lib::{Action, Problem, ScreenAction, View, ViewAllProblemsScreen};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crate::db::{self, PROBLEM_COLUMNS, problem_from_row};
+use crate::lib::{
+    Action, ListPrompt, Problem, ProblemFilter, Relation, RelationKind, ScreenAction, View,
+    ViewAllProblemsScreen,
+};
+use crate::scheduler::snooze;
+use crate::screens::deck_screen::scoped_title;
+use crate::screens::notes_screen::markdown_lines;
+use crate::stopwatch::format_duration;
+use chrono::{DateTime, Utc};
+use crossterm::event::KeyCode;
 use ratatui::Frame;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -12,46 +21,294 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Cell, Table, TableState};
 use ratatui::widgets::{Borders, Paragraph};
 use ratatui::widgets::{Row, Widget};
-use std::io;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 impl<'a> ViewAllProblemsScreen<'a> {
     pub fn new(db: Arc<rusqlite::Connection>) -> Self {
         let mut list_state = TableState::default();
         list_state.select(Some(0));
-        let items = query_items(&db).unwrap();
+        let filter = ProblemFilter::Active;
+        let solve_times = db::solve_times(&db).unwrap_or_default();
+        let (items, problem_ids) = query_items(&db, filter, &solve_times).unwrap_or_default();
+        let notes = db::problem_notes(&db).unwrap_or_default();
+        let relations = db::problem_relations(&db).unwrap_or_default();
+        let deck = db::current_deck(&db).ok().flatten();
 
         Self {
             db,
             items,
+            problem_ids,
             list_state,
+            filter,
+            deck,
+            notes,
+            solve_times,
+            relations,
+            prompt: None,
+            message: None,
         }
     }
+
+    /// The list as it was left, coming back from a problem's notes.
+    pub fn showing(db: Arc<rusqlite::Connection>, filter: ProblemFilter, problem_id: i64) -> Self {
+        let mut screen = Self::new(db);
+        screen.filter = filter;
+        screen.reload();
+        let selected = screen.problem_ids.iter().position(|id| *id == problem_id);
+        screen.list_state.select(Some(selected.unwrap_or(0)));
+        screen
+    }
+
+    pub fn selected_id(&self) -> Option<i64> {
+        self.problem_ids.get(self.list_state.selected()?).copied()
+    }
+
+    fn selected(&self) -> Option<Problem> {
+        db::load_problem(&self.db, self.selected_id()?)
+            .ok()
+            .flatten()
+    }
+
+    fn apply_prompt(&mut self, prompt: ListPrompt, input: &str) {
+        match prompt {
+            ListPrompt::DueDate => self.apply_due_input(input),
+            ListPrompt::Rename => self.apply_rename(input),
+            ListPrompt::Link => self.apply_link(input),
+        }
+        self.reload();
+    }
+
+    /// Snoozes or pins the selected problem using what was typed at the prompt.
+    fn apply_due_input(&mut self, input: &str) {
+        let Some(problem) = self.selected() else {
+            return;
+        };
+        let now = Utc::now();
+        let due_override = match snooze::parse_due_override(input, now.date_naive()) {
+            Ok(due_override) => due_override,
+            Err(error) => {
+                self.message = Some(error);
+                return;
+            }
+        };
+        let name = problem.name;
+        self.message = match snooze::apply_due_override(&self.db, problem.id, due_override, now) {
+            Ok(Some(due_date)) => Some(format!(
+                "{name} is now due {}",
+                due_date.date_naive().format("%Y-%m-%d")
+            )),
+            Ok(None) => None,
+            Err(error) => Some(format!("There was an error moving {name}: {error}")),
+        };
+    }
+
+    fn apply_rename(&mut self, input: &str) {
+        let Some(problem) = self.selected() else {
+            return;
+        };
+        let new_name = db::normalize_name(input);
+        self.message = if new_name.is_empty() {
+            Some("A problem needs a name".to_string())
+        } else {
+            match db::rename_problem(&self.db, problem.id, &new_name) {
+                Ok(true) => Some(format!("{} renamed to {new_name}", problem.name)),
+                Ok(false) => Some(format!("There is already a problem called {new_name}")),
+                Err(error) => Some(format!(
+                    "There was an error renaming {}: {error}",
+                    problem.name
+                )),
+            }
+        };
+    }
+
+    /// Links the selected problem using what was typed at the prompt: a
+    /// relation kind and the other problem's name, such as "follow-up Two
+    /// Sum", or "unlink" and a name to remove the links between the two.
+    fn apply_link(&mut self, input: &str) {
+        let Some(problem) = self.selected() else {
+            return;
+        };
+        let input = input.trim();
+        let (word, name) = input.split_once(char::is_whitespace).unwrap_or((input, ""));
+        let name = db::normalize_name(name);
+        if name.is_empty() {
+            self.message = Some("Name the problem to link to after the relation".to_string());
+            return;
+        }
+        let related_id = match db::find_problem_id(&self.db, &name) {
+            Ok(Some(related_id)) if related_id == problem.id => {
+                self.message = Some("A problem can't be linked to itself".to_string());
+                return;
+            }
+            Ok(Some(related_id)) => related_id,
+            Ok(None) => {
+                self.message = Some(format!("There is no problem called {name}"));
+                return;
+            }
+            Err(error) => {
+                self.message = Some(format!("There was an error looking up {name}: {error}"));
+                return;
+            }
+        };
+        let name = db::load_problem(&self.db, related_id)
+            .ok()
+            .flatten()
+            .map_or(name, |related| related.name);
+
+        if word.eq_ignore_ascii_case("unlink") {
+            self.message = match db::unlink_problems(&self.db, problem.id, related_id) {
+                Ok(0) => Some(format!("{} and {name} weren't linked", problem.name)),
+                Ok(_) => Some(format!("{} and {name} are no longer linked", problem.name)),
+                Err(error) => Some(format!(
+                    "There was an error unlinking {}: {error}",
+                    problem.name
+                )),
+            };
+            return;
+        }
+        let Some(kind) = RelationKind::parse(word) else {
+            self.message =
+                Some("Start with similar, follow-up, prerequisite or unlink".to_string());
+            return;
+        };
+        let relation = Relation {
+            kind,
+            incoming: false,
+            problem_id: related_id,
+            problem_name: name,
+        };
+        let linked = format!(
+            "{} {} {}",
+            problem.name,
+            relation.label().to_lowercase(),
+            relation.problem_name
+        );
+        self.message = match db::link_problems(&self.db, problem.id, related_id, kind) {
+            Ok(true) => Some(format!("Linked: {linked}")),
+            Ok(false) => Some(format!("Already linked: {linked}")),
+            Err(error) => Some(format!(
+                "There was an error linking {}: {error}",
+                problem.name
+            )),
+        };
+    }
+
+    /// Suspends the selected active or mastered problem, or resumes the
+    /// selected suspended one.
+    fn toggle_suspended(&mut self) {
+        let Some(problem) = self.selected() else {
+            return;
+        };
+        let name = problem.name;
+        let suspend = self.filter != ProblemFilter::Suspended;
+        self.message = match db::set_suspended(&self.db, problem.id, suspend) {
+            Ok(()) if suspend => Some(format!("{name} suspended")),
+            Ok(()) => Some(format!("{name} is back in review")),
+            Err(error) => Some(format!("There was an error suspending {name}: {error}")),
+        };
+        self.reload();
+    }
+
+    /// Re-reads the list after switching filters or changing one of the
+    /// problems.
+    fn reload(&mut self) {
+        if let Ok(solve_times) = db::solve_times(&self.db) {
+            self.solve_times = solve_times;
+        }
+        if let Ok((items, problem_ids)) = query_items(&self.db, self.filter, &self.solve_times) {
+            self.items = items;
+            self.problem_ids = problem_ids;
+        }
+        if let Ok(notes) = db::problem_notes(&self.db) {
+            self.notes = notes;
+        }
+        if let Ok(relations) = db::problem_relations(&self.db) {
+            self.relations = relations;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state
+            .select(Some(selected.min(self.items.len().saturating_sub(1))));
+    }
 }
 
-fn query_items(db: &Arc<rusqlite::Connection>) -> rusqlite::Result<Vec<Row<'static>>> {
-    let mut db_result = db.prepare("SELECT * FROM user_problems")?;
+fn query_items(
+    db: &Arc<rusqlite::Connection>,
+    filter: ProblemFilter,
+    solve_times: &HashMap<i64, Vec<i64>>,
+) -> rusqlite::Result<(Vec<Row<'static>>, Vec<i64>)> {
+    let condition = match filter {
+        ProblemFilter::Active => "mastered = 0 AND suspended = 0",
+        ProblemFilter::Mastered => "mastered = 1 AND suspended = 0",
+        ProblemFilter::Suspended => "suspended = 1",
+    };
+    let mut db_result = db.prepare(&format!(
+        "SELECT {PROBLEM_COLUMNS} FROM user_problems WHERE {condition}"
+    ))?;
 
-    let problem_iter = db_result.query_map([], |row| {
-        Ok(Problem {
-            name: row.get(0)?,
-            rating: row.get(1)?,
-            entry_date: row.get(2)?,
-        })
-    })?;
+    let problem_iter = db_result.query_map([], problem_from_row)?;
+    let scope = db::deck_scope(db)?;
+    let review_counts = db::review_counts(db)?;
+    let mut tags = db::problem_tags(db)?;
     let mut items: Vec<Row> = vec![];
+    let mut problem_ids: Vec<i64> = vec![];
     for problem_result in problem_iter {
         let problem = problem_result?;
+        if !db::in_scope(&scope, problem.id) {
+            continue;
+        }
+        problem_ids.push(problem.id);
+        let rating = db::rating_label(&review_counts, &problem);
+        let metadata = problem.metadata;
         let cells = vec![
             Cell::from(problem.name),
-            Cell::from(problem.rating),
-            Cell::from(problem.entry_date),
+            Cell::from(rating),
+            Cell::from(metadata.platform.unwrap_or_default()),
+            Cell::from(metadata.number.unwrap_or_default()),
+            Cell::from(
+                metadata
+                    .difficulty
+                    .map_or("", |difficulty| difficulty.as_str()),
+            ),
+            Cell::from(short_date(Some(problem.entry_date))),
+            Cell::from(short_date(problem.due_date)),
+            Cell::from(last_solve_time(solve_times.get(&problem.id))),
+            Cell::from(tag_chips(tags.remove(&problem.id).unwrap_or_default())),
+            Cell::from(metadata.url.unwrap_or_default()),
         ];
         let row = Row::new(cells).height(2);
         items.push(row);
     }
 
-    Ok(items)
+    Ok((items, problem_ids))
+}
+
+fn tag_chips(tags: Vec<String>) -> Line<'static> {
+    let mut chips = Line::default();
+    for tag in tags {
+        chips.push_span(Span::styled(
+            format!(" {tag} "),
+            Style::default().fg(Color::Black).bg(Color::LightBlue),
+        ));
+        chips.push_span(" ");
+    }
+    chips
+}
+
+/// The latest solve time, with an arrow showing whether it beat the one
+/// before.
+fn last_solve_time(times: Option<&Vec<i64>>) -> String {
+    match times.map(Vec::as_slice).unwrap_or_default() {
+        [] => String::new(),
+        [.., previous, last] if last < previous => format!("{} ↓", format_duration(*last)),
+        [.., previous, last] if last > previous => format!("{} ↑", format_duration(*last)),
+        [.., last] => format_duration(*last),
+    }
+}
+
+/// Just the day of a timestamp, to leave room for the other columns.
+fn short_date(date: Option<DateTime<Utc>>) -> String {
+    date.map_or(String::new(), |date| date.format("%Y-%m-%d").to_string())
 }
 
 impl<'a> View for ViewAllProblemsScreen<'a> {
@@ -60,6 +317,27 @@ impl<'a> View for ViewAllProblemsScreen<'a> {
     }
 
     fn handle_key_event(&mut self, key_event: crossterm::event::KeyEvent) -> Action {
+        if let Some((prompt, input)) = self.prompt.as_mut() {
+            match key_event.code {
+                KeyCode::Char(c)
+                    if *prompt != ListPrompt::DueDate || c.is_ascii_digit() || c == '-' =>
+                {
+                    input.push(c)
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    if let Some((prompt, input)) = self.prompt.take() {
+                        self.apply_prompt(prompt, &input);
+                    }
+                }
+                KeyCode::Esc => self.prompt = None,
+                _ => {}
+            }
+            return Action::NoOp;
+        }
+
         match key_event.code {
             KeyCode::Char('q') => Action::Quit, // Global
             KeyCode::Esc => Action::ShouldSwitch,
@@ -67,6 +345,56 @@ impl<'a> View for ViewAllProblemsScreen<'a> {
             KeyCode::Down => Action::ScreenSpecific(ScreenAction::MenuNext),
             KeyCode::Up => Action::ScreenSpecific(ScreenAction::MenuPrev),
             KeyCode::Enter => Action::ScreenSpecific(ScreenAction::MenuSelect),
+            KeyCode::Tab => {
+                self.filter = match self.filter {
+                    ProblemFilter::Active => ProblemFilter::Mastered,
+                    ProblemFilter::Mastered => ProblemFilter::Suspended,
+                    ProblemFilter::Suspended => ProblemFilter::Active,
+                };
+                self.message = None;
+                self.list_state.select(Some(0));
+                self.reload();
+                Action::NoOp
+            }
+            KeyCode::Char('s') if self.filter == ProblemFilter::Active => {
+                if self.selected().is_some() {
+                    self.message = None;
+                    self.prompt = Some((ListPrompt::DueDate, String::new()));
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('r') => {
+                if let Some(problem) = self.selected() {
+                    self.message = None;
+                    self.prompt = Some((ListPrompt::Rename, problem.name));
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('l') => {
+                if self.selected_id().is_some() {
+                    self.message = None;
+                    self.prompt = Some((ListPrompt::Link, String::new()));
+                }
+                Action::NoOp
+            }
+            KeyCode::Char('c') => Action::ScreenSpecific(ScreenAction::ViewSolutions),
+            KeyCode::Char('x') => {
+                self.toggle_suspended();
+                Action::NoOp
+            }
+            KeyCode::Char('u') if self.filter == ProblemFilter::Mastered => {
+                if let Some(problem) = self.selected() {
+                    let name = problem.name;
+                    self.message = match db::unmaster_problem(&self.db, problem.id) {
+                        Ok(()) => Some(format!("{name} is back in review")),
+                        Err(error) => {
+                            Some(format!("There was an error un-mastering {name}: {error}"))
+                        }
+                    };
+                    self.reload();
+                }
+                Action::NoOp
+            }
             _ => Action::NoOp,
         }
     }
@@ -74,7 +402,7 @@ impl<'a> View for ViewAllProblemsScreen<'a> {
 
 impl<'a> Widget for &ViewAllProblemsScreen<'a> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let instructions = Line::from(vec![
+        let mut instructions = Line::from(vec![
             " Quit ".into(),
             Span::styled(
                 "<Q>",
@@ -89,7 +417,7 @@ impl<'a> Widget for &ViewAllProblemsScreen<'a> {
                     .fg(Color::Blue)
                     .add_modifier(Modifier::BOLD),
             ),
-            " Enter".into(),
+            " Notes ".into(),
             Span::styled(
                 "<Enter>",
                 Style::default()
@@ -103,13 +431,79 @@ impl<'a> Widget for &ViewAllProblemsScreen<'a> {
                     .fg(Color::Blue)
                     .add_modifier(Modifier::BOLD),
             ),
+            match self.filter {
+                ProblemFilter::Active => " Mastered ".into(),
+                ProblemFilter::Mastered => " Suspended ".into(),
+                ProblemFilter::Suspended => " Active ".into(),
+            },
+            Span::styled(
+                "<Tab>",
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ),
         ]);
+        match self.filter {
+            ProblemFilter::Active => {
+                instructions.push_span(" Snooze/Pin ");
+                instructions.push_span(Span::styled(
+                    "<S>",
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            ProblemFilter::Mastered => {
+                instructions.push_span(" Un-master ");
+                instructions.push_span(Span::styled(
+                    "<U>",
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            ProblemFilter::Suspended => {}
+        }
+        instructions.push_span(if self.filter == ProblemFilter::Suspended {
+            " Resume "
+        } else {
+            " Suspend "
+        });
+        instructions.push_span(Span::styled(
+            "<X>",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ));
+        instructions.push_span(" Code ");
+        instructions.push_span(Span::styled(
+            "<C>",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ));
+        instructions.push_span(" Rename ");
+        instructions.push_span(Span::styled(
+            "<R>",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ));
+        instructions.push_span(" Link ");
+        instructions.push_span(Span::styled(
+            "<L>",
+            Style::default()
+                .fg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+        ));
 
         let container_block = Block::default()
             .borders(Borders::ALL)
             .border_set(border::THICK)
             .border_style(Style::default().fg(Color::Cyan))
-            .title_top(Line::from(" 🔎 Problem Database Explorer ").centered())
+            .title_top(
+                scoped_title(" 🔎 Problem Database Explorer ", self.deck.as_ref()).centered(),
+            )
             .title_bottom(instructions.centered());
         container_block.clone().render(area, buf);
 
@@ -119,21 +513,45 @@ impl<'a> Widget for &ViewAllProblemsScreen<'a> {
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Length(3),
-                Constraint::Length(10),
-                Constraint::Min(0),
+                Constraint::Min(10),
+                Constraint::Length(12),
             ])
             .split(inner_area);
 
         let welcome_area = chunks[0];
         //let menu_area = chunks[1];
 
-        let welcome_text = Text::from(vec![Line::from(Span::styled(
-            "View all problems added to database!",
+        let mut welcome_lines = vec![Line::from(Span::styled(
+            match self.filter {
+                ProblemFilter::Active => "View all problems added to database!",
+                ProblemFilter::Mastered => "Mastered problems, retired from review",
+                ProblemFilter::Suspended => "Suspended problems, parked until resumed",
+            },
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
-        ))])
-        .centered();
+        ))];
+        if let Some((prompt, input)) = &self.prompt {
+            welcome_lines.push(Line::from(vec![
+                match prompt {
+                    ListPrompt::DueDate => "Snooze by days or pin to YYYY-MM-DD: ".into(),
+                    ListPrompt::Rename => "New name: ".into(),
+                    ListPrompt::Link => {
+                        "similar / follow-up / prerequisite / unlink, then a problem: ".into()
+                    }
+                },
+                Span::styled(
+                    format!("{input}█"),
+                    Style::default()
+                        .fg(Color::Green)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                " (Enter to apply, Esc to cancel)".into(),
+            ]));
+        } else if let Some(message) = &self.message {
+            welcome_lines.push(Line::from(message.as_str()));
+        }
+        let welcome_text = Text::from(welcome_lines).centered();
 
         Paragraph::new(welcome_text)
             .alignment(ratatui::layout::Alignment::Center)
@@ -145,20 +563,91 @@ impl<'a> Widget for &ViewAllProblemsScreen<'a> {
             .add_modifier(Modifier::BOLD);
 
         let widths = [
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
-            Constraint::Percentage(33),
+            Constraint::Percentage(16),
+            Constraint::Percentage(6),
+            Constraint::Percentage(9),
+            Constraint::Percentage(7),
+            Constraint::Percentage(8),
+            Constraint::Percentage(10),
+            Constraint::Percentage(10),
+            Constraint::Percentage(7),
+            Constraint::Percentage(16),
+            Constraint::Percentage(11),
         ];
         let rows = self.items.clone();
 
         let table = Table::new(rows, widths)
-            .header(Row::new(vec!["Problem Name", "Problem Rating", "Last Entry"]).bottom_margin(1))
-            .column_spacing(20)
+            .header(
+                Row::new(vec![
+                    "Problem Name",
+                    "Rating",
+                    "Platform",
+                    "Number",
+                    "Difficulty",
+                    "Last Entry",
+                    "Next Review",
+                    "Time",
+                    "Tags",
+                    "URL",
+                ])
+                .bottom_margin(1),
+            )
+            .column_spacing(2)
             .row_highlight_style(selection_style)
             .highlight_symbol(">> ");
 
         let mut temp_state = self.list_state.clone();
 
         ratatui::widgets::StatefulWidget::render(table, chunks[1], buf, &mut temp_state);
+
+        let mut details = vec![];
+        if let Some(times) = self.selected_id().and_then(|id| self.solve_times.get(&id)) {
+            let history: Vec<String> = times.iter().map(|time| format_duration(*time)).collect();
+            let best = times.iter().min().copied().unwrap_or_default();
+            details.push(Line::from(vec![
+                Span::styled(
+                    "Solve times: ",
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                format!("{} (best {})", history.join(" → "), format_duration(best)).into(),
+            ]));
+        }
+        if let Some(relations) = self.selected_id().and_then(|id| self.relations.get(&id)) {
+            let mut related = vec![Span::styled(
+                "Related: ",
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            )];
+            for (index, relation) in relations.iter().enumerate() {
+                if index > 0 {
+                    related.push(" · ".into());
+                }
+                related.push(format!("{} ", relation.label()).into());
+                related.push(Span::styled(
+                    relation.problem_name.clone(),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ));
+            }
+            details.push(Line::from(related));
+        }
+        match self.selected_id().and_then(|id| self.notes.get(&id)) {
+            Some(notes) => details.extend(markdown_lines(notes)),
+            None => details.push(Line::styled(
+                "No notes yet, press Enter to write some",
+                Style::default().fg(Color::DarkGray),
+            )),
+        }
+        Paragraph::new(details)
+            .wrap(ratatui::widgets::Wrap { trim: false })
+            .block(
+                Block::default()
+                    .borders(Borders::TOP)
+                    .title(" Details ")
+                    .border_style(Style::default().fg(Color::Cyan)),
+            )
+            .render(chunks[2], buf);
     }
 }
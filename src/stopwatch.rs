@@ -0,0 +1,42 @@
+use crate::lib::Stopwatch;
+use std::time::{Duration, Instant};
+
+impl Stopwatch {
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Starts the stopwatch, or pauses it if it is already running.
+    pub fn toggle(&mut self) {
+        match self.started.take() {
+            Some(started) => self.banked += started.elapsed(),
+            None => self.started = Some(Instant::now()),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.banked
+            + self
+                .started
+                .map_or(Duration::ZERO, |started| started.elapsed())
+    }
+
+    /// Whole seconds timed, or `None` if the stopwatch was never started.
+    pub fn seconds(&self) -> Option<i64> {
+        (self.is_running() || !self.banked.is_zero()).then(|| self.elapsed().as_secs() as i64)
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour up.
+pub fn format_duration(seconds: i64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}